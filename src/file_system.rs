@@ -6,12 +6,31 @@
 //! - de parcourir des répertoires
 //! - de gérer les noms courts (8.3) et les Long File Names (LFN)
 //! - de lire le contenu d’un fichier texte via son chemin
+pub mod check;
+pub mod clock;
+pub mod copy;
+pub mod device;
+pub mod error;
 pub mod interface;
+pub mod options;
+pub mod tree;
 
+pub use check::{CheckOptions, FsReport, FsWarning};
+pub use clock::{FatClock, FatTimestamp};
+pub use copy::Progress;
+pub use device::ReadHook;
+pub use error::FsError;
+pub use options::{AllocStrategy, MountOptions};
+
+use alloc::borrow::Cow;
 use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::rc::Rc;
 use alloc::string::ToString;
 use alloc::vec;
 use alloc::{string::String, vec::Vec};
+use core::cell::Cell;
 
 /// Représente un système de fichiers FAT32 monté en mémoire
 #[derive(Debug, Clone)]
@@ -28,13 +47,126 @@ pub struct Fat32FileSystem {
     /// Premier secteur de la FAT.
     pub fat_sector: u32,
 
+    /// Nombre de copies de la FAT.
+    pub num_fats: u32,
+
+    /// Nombre de secteurs occupés par une copie de la FAT.
+    pub sectors_per_fat: u32,
+
     /// Premier secteur de la zone de données.
     pub data_sector: u32,
 
     /// Cluster racine du système de fichiers.
     pub root_cluster: u32,
+
+    /// Secteur de la structure FSInfo (`0` si absente).
+    pub fsinfo_sector: u32,
+
+    /// Secteur de la copie de secours du secteur de boot (`0` si absente).
+    pub backup_boot_sector: u32,
+
+    /// Options de montage (tolérance, performances, écriture), voir [`MountOptions`].
+    pub options: MountOptions,
+
+    /// Cluster suivant le dernier cluster alloué, point de départ de
+    /// [`AllocStrategy::NextFit`] (`None` avant la première allocation : le FSInfo sert alors).
+    alloc_cursor: Option<u32>,
+
+    /// Nombre de clusters libres, calculé au premier appel de [`Self::free_count_cached`]
+    /// puis tenu à jour par [`Self::write_fat_entry`] (`None` tant qu’il est inconnu).
+    free_count: Cell<Option<u32>>,
+
+    /// Observateur des lectures fourni par le périphérique de blocs (voir [`ReadHook`]).
+    read_hook: Option<Rc<dyn ReadHook>>,
+
+    /// Horloge utilisée pour horodater les entrées (époque FAT si absente).
+    clock: Option<Rc<dyn FatClock>>,
+}
+
+/// Limite de lecture par défaut d’un fichier (un cinquième du tas de 40 Mio du noyau).
+pub const DEFAULT_MAX_READ_BYTES: usize = 8 * 1024 * 1024;
+
+/// Entrée FAT d’un cluster libre.
+const FAT_FREE: u32 = 0;
+/// Entrée FAT d’un cluster défectueux.
+const FAT_BAD: u32 = 0x0FFFFFF7;
+/// Plus petite valeur d’entrée FAT marquant la fin d’une chaîne.
+const FAT_EOC: u32 = 0x0FFFFFF8;
+/// Valeur écrite dans la FAT pour terminer une chaîne.
+const FAT_EOC_MARK: u32 = 0x0FFFFFFF;
+/// Masque des 28 bits significatifs d’une entrée FAT32.
+const FAT_ENTRY_MASK: u32 = 0x0FFFFFFF;
+
+/// Indique si une entrée FAT marque la fin d’une chaîne (`0x0FFFFFF8..=0x0FFFFFFF`).
+///
+/// Les 4 bits de poids fort, réservés, sont ignorés.
+fn is_end_of_chain(entry: u32) -> bool {
+    entry & FAT_ENTRY_MASK >= FAT_EOC
+}
+
+/// Attribut d’entrée : lecture seule.
+pub const ATTR_READ_ONLY: u8 = 0x01;
+/// Attribut d’entrée : caché.
+pub const ATTR_HIDDEN: u8 = 0x02;
+/// Attribut d’entrée : système.
+pub const ATTR_SYSTEM: u8 = 0x04;
+/// Attribut d’entrée : étiquette du volume.
+pub const ATTR_VOLUME_ID: u8 = 0x08;
+/// Attribut d’entrée : répertoire.
+pub const ATTR_DIRECTORY: u8 = 0x10;
+/// Attribut d’entrée : archive (fichier modifié).
+pub const ATTR_ARCHIVE: u8 = 0x20;
+/// Combinaison d’attributs marquant un fragment de nom long (LFN).
+pub const ATTR_LFN: u8 = ATTR_READ_ONLY | ATTR_HIDDEN | ATTR_SYSTEM | ATTR_VOLUME_ID;
+
+/// Nature d’une entrée de répertoire, déterminée une seule fois par [`EntryKind::classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    LongName,
+    VolumeLabel,
+    Directory,
+    File,
+}
+
+impl EntryKind {
+    /// Classe une entrée d’après son octet d’attributs.
+    ///
+    /// Un fragment LFN se reconnaît à la combinaison exacte [`ATTR_LFN`] ; sinon
+    /// l’étiquette du volume prime sur le répertoire, qui prime sur le fichier.
+    fn classify(attr: u8) -> EntryKind {
+        if attr & 0x3F == ATTR_LFN {
+            EntryKind::LongName
+        } else if attr & ATTR_VOLUME_ID != 0 {
+            EntryKind::VolumeLabel
+        } else if attr & ATTR_DIRECTORY != 0 {
+            EntryKind::Directory
+        } else {
+            EntryKind::File
+        }
+    }
 }
 
+/// Table du CRC32 (polynôme réfléchi `0xEDB88320`), calculée à la compilation.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
 /// Offsets (en octets) dans le secteur de boot FAT32.
 ///
 /// Ces valeurs sont définies par la spécification FAT32.
@@ -44,8 +176,13 @@ enum BootOffsets {
     SecPerClus = 13,
     RsvdSecCnt = 14,
     NumFATs = 16,
+    TotSec16 = 19,
+    FATSz16 = 22,
+    TotSec32 = 32,
     FATSz32 = 36,
     RootClus = 44,
+    FSInfo = 48,
+    BkBootSec = 50,
 }
 
 impl Fat32FileSystem {
@@ -67,283 +204,2186 @@ impl Fat32FileSystem {
     /// - parse le secteur de boot,
     /// - calcule les offsets FAT et data,
     /// - identifie le cluster racine.
+    ///
+    /// # Panics
+    /// Panique si la géométrie du volume est invalide (voir [`Self::try_new`]).
     pub fn new(disk: Box<[u8]>) -> Self {
+        Self::new_with_options(disk, MountOptions::default())
+    }
+
+    /// Initialise un système de fichiers FAT32 avec les options de montage données.
+    ///
+    /// # Panics
+    /// Panique si la géométrie du volume est invalide (voir [`Self::try_new`]).
+    pub fn new_with_options(disk: Box<[u8]>, opts: MountOptions) -> Self {
+        Self::try_new_with_options(disk, opts).expect("Invalid FAT32 volume")
+    }
+
+    /// Initialise un système de fichiers FAT32 avec les options de montage données, en
+    /// validant sa géométrie.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::try_new`]
+    /// - [`FsError::BadGeometry`] si [`MountOptions::strict_geometry`] est activé et que la
+    ///   longueur de l’image n’est pas un multiple de la taille de secteur
+    /// - [`FsError::TruncatedImage`] si [`MountOptions::strict_geometry`] est activé et que
+    ///   l’image est plus courte que le volume décrit par le secteur de boot
+    ///   (voir [`Self::missing_bytes`])
+    /// - Erreurs de [`Self::verify_layout`] si [`MountOptions::strict_geometry`] est activé
+    pub fn try_new_with_options(disk: Box<[u8]>, opts: MountOptions) -> Result<Self, FsError> {
+        let mut fs = Self::try_new(disk)?;
+        fs.set_mount_options(opts);
+
+        if fs.options.strict_geometry && fs.trailing_bytes() != 0 {
+            return Err(FsError::BadGeometry);
+        }
+        if fs.options.strict_geometry && fs.missing_bytes() != 0 {
+            return Err(FsError::TruncatedImage);
+        }
+        if fs.options.strict_geometry {
+            fs.verify_layout()?;
+        }
+
+        Ok(fs)
+    }
+
+    /// Vérifie que les régions du volume se suivent sans se chevaucher et tiennent dans
+    /// l’image : la région réservée (secteur de boot, FSInfo, secteur de boot de secours)
+    /// précède les FAT, qui précèdent immédiatement la zone de données.
+    ///
+    /// Un secteur de boot corrompu peut sinon faire lire à [`Self::read_fat_entry`] et à
+    /// [`Self::read_cluster`] les données d’une autre région.
+    ///
+    /// # Errors
+    /// Retourne [`FsError::BadLayout`] avec la région fautive :
+    /// - `"reserved"` si la FAT débute au secteur 0, ou si le FSInfo ou le secteur de boot
+    ///   de secours est situé hors de la région réservée
+    /// - `"fat"` si les FAT ne s’arrêtent pas exactement au début de la zone de données
+    /// - `"data"` si la zone de données débute au-delà de la fin de l’image
+    pub fn verify_layout(&self) -> Result<(), FsError> {
+        let reserved_overlap = [self.fsinfo_sector, self.backup_boot_sector]
+            .into_iter()
+            .filter(|&sector| sector != 0 && sector != 0xFFFF)
+            .any(|sector| sector >= self.fat_sector);
+        if self.fat_sector == 0 || reserved_overlap {
+            return Err(FsError::BadLayout { region: "reserved" });
+        }
+
+        let fat_end = self.fat_sector as u64 + self.num_fats as u64 * self.sectors_per_fat as u64;
+        if fat_end != self.data_sector as u64 {
+            return Err(FsError::BadLayout { region: "fat" });
+        }
+
+        let data_start = self.data_sector as u64 * self.bytes_per_sector as u64;
+        if data_start > self.disk.len() as u64 {
+            return Err(FsError::BadLayout { region: "data" });
+        }
+
+        Ok(())
+    }
+
+    /// Retourne le nombre total de secteurs du volume d’après le secteur de boot
+    /// (`BPB_TotSec32`, ou `BPB_TotSec16` s’il est nul).
+    pub fn total_sectors(&self) -> u32 {
+        match self.disk.get(..512) {
+            Some(boot) => match Self::read_u32(boot, BootOffsets::TotSec32) {
+                0 => Self::read_u16(boot, BootOffsets::TotSec16) as u32,
+                count => count,
+            },
+            None => 0,
+        }
+    }
+
+    /// Retourne le nombre d’octets manquants en fin d’image par rapport au volume décrit
+    /// par le secteur de boot (image partiellement téléchargée ou copiée).
+    ///
+    /// Les données situées dans la partie manquante ne sont lisibles qu’avec
+    /// [`MountOptions::allow_short_reads`].
+    pub fn missing_bytes(&self) -> u64 {
+        (self.total_sectors() as u64 * self.bytes_per_sector as u64)
+            .saturating_sub(self.disk.len() as u64)
+    }
+
+    /// Retourne le nombre d’octets en fin d’image qui ne forment pas un secteur complet.
+    ///
+    /// Ces octets sont inaccessibles et indiquent une géométrie suspecte.
+    pub fn trailing_bytes(&self) -> usize {
+        match self.bytes_per_sector {
+            0 => 0,
+            size => self.disk.len() % size as usize,
+        }
+    }
+
+    /// Initialise un système de fichiers FAT32 en validant sa géométrie.
+    ///
+    /// # Errors
+    /// Retourne [`FsError::BadGeometry`] si la taille de secteur n’est pas un multiple
+    /// non nul de 4 (une entrée FAT doit tenir dans un seul secteur), si un cluster
+    /// ne contient aucun secteur ou si la taille de la FAT est nulle (`BPB_FATSz32` et
+    /// `BPB_FATSz16`).
+    pub fn try_new(disk: Box<[u8]>) -> Result<Self, FsError> {
         let bytes_per_sector = Self::read_u16(&disk, BootOffsets::BytsPerSec) as u32;
         let sectors_per_cluster = disk[BootOffsets::SecPerClus as usize] as u32;
         let reserved_sectors_count = Self::read_u16(&disk, BootOffsets::RsvdSecCnt) as u32;
         let num_fats = disk[BootOffsets::NumFATs as usize] as u32;
-        let sectors_per_fat = Self::read_u32(&disk, BootOffsets::FATSz32);
+        // `BPB_FATSz16` n’est légal qu’en FAT12/16, mais certains outils le remplissent à la
+        // place de `BPB_FATSz32`
+        let sectors_per_fat = match Self::read_u32(&disk, BootOffsets::FATSz32) {
+            0 => Self::read_u16(&disk, BootOffsets::FATSz16) as u32,
+            size => size,
+        };
         let root_cluster = Self::read_u32(&disk, BootOffsets::RootClus);
+        let fsinfo_sector = Self::read_u16(&disk, BootOffsets::FSInfo) as u32;
+        let backup_boot_sector = Self::read_u16(&disk, BootOffsets::BkBootSec) as u32;
+
+        if bytes_per_sector == 0
+            || !bytes_per_sector.is_multiple_of(4)
+            || sectors_per_cluster == 0
+            || sectors_per_fat == 0
+        {
+            return Err(FsError::BadGeometry);
+        }
 
         let fat_sector = reserved_sectors_count;
         let data_sector = reserved_sectors_count + num_fats * sectors_per_fat;
 
+        Ok(Fat32FileSystem {
+            disk,
+            bytes_per_sector,
+            sectors_per_cluster,
+            fat_sector,
+            num_fats,
+            sectors_per_fat,
+            data_sector,
+            root_cluster,
+            fsinfo_sector,
+            backup_boot_sector,
+            options: MountOptions::default(),
+            alloc_cursor: None,
+            free_count: Cell::new(None),
+            read_hook: None,
+            clock: None,
+        })
+    }
+
+    /// Construit un système de fichiers à partir de paramètres déjà connus.
+    ///
+    /// Le secteur de boot n’est pas parsé : les champs sont repris tels quels et
+    /// une seule FAT occupant l’espace entre `fat_sector` et `data_sector` est supposée.
+    /// Permet aux tests de construire de petits volumes synthétiques (quelques clusters)
+    /// sans passer par une image FAT32 complète.
+    pub fn new_from_parts(
+        disk: Box<[u8]>,
+        bytes_per_sector: u32,
+        sectors_per_cluster: u32,
+        fat_sector: u32,
+        data_sector: u32,
+        root_cluster: u32,
+    ) -> Self {
         Fat32FileSystem {
             disk,
             bytes_per_sector,
             sectors_per_cluster,
             fat_sector,
+            num_fats: 1,
+            sectors_per_fat: data_sector - fat_sector,
             data_sector,
             root_cluster,
+            fsinfo_sector: 0,
+            backup_boot_sector: 0,
+            options: MountOptions::default(),
+            alloc_cursor: None,
+            free_count: Cell::new(None),
+            read_hook: None,
+            clock: None,
+        }
+    }
+
+    /// Vérifie que le volume accepte les écritures.
+    ///
+    /// # Errors
+    /// Retourne [`FsError::ReadOnly`] si le volume est monté en lecture seule.
+    fn ensure_writable(&self) -> Result<(), FsError> {
+        if self.options.read_only {
+            return Err(FsError::ReadOnly);
         }
+        Ok(())
+    }
+
+    /// Définit l’observateur prévenu de chaque lecture de secteurs (voir [`ReadHook`]).
+    pub fn set_read_hook(&mut self, hook: Rc<dyn ReadHook>) {
+        self.read_hook = Some(hook);
+    }
+
+    /// Définit l’horloge utilisée pour horodater les créations et modifications.
+    pub fn set_clock(&mut self, clock: Rc<dyn FatClock>) {
+        self.clock = Some(clock);
+    }
+
+    /// Retourne la date et l’heure courantes, ou l’époque FAT si aucune horloge n’est définie.
+    pub fn now(&self) -> FatTimestamp {
+        self.clock
+            .as_ref()
+            .map_or(FatTimestamp::EPOCH, |clock| clock.now())
+    }
+
+    /// Renseigne les dates de création, de modification et d’accès d’une entrée neuve.
+    fn stamp_new_entry(&self, entry: &mut [u8]) {
+        let now = self.now();
+        let date = now.to_fat_date().to_le_bytes();
+        let time = now.to_fat_time().to_le_bytes();
+
+        entry[DirOffsets::CrtTimeTenth as usize] = (now.second % 2) * 100;
+        entry[DirOffsets::CrtTime as usize..DirOffsets::CrtTime as usize + 2]
+            .copy_from_slice(&time);
+        entry[DirOffsets::CrtDate as usize..DirOffsets::CrtDate as usize + 2]
+            .copy_from_slice(&date);
+        entry[DirOffsets::LstAccDate as usize..DirOffsets::LstAccDate as usize + 2]
+            .copy_from_slice(&date);
+        entry[DirOffsets::WrtTime as usize..DirOffsets::WrtTime as usize + 2]
+            .copy_from_slice(&time);
+        entry[DirOffsets::WrtDate as usize..DirOffsets::WrtDate as usize + 2]
+            .copy_from_slice(&date);
+    }
+
+    /// Met à jour les dates de modification et d’accès d’une entrée existante.
+    ///
+    /// La date de création est conservée.
+    ///
+    /// # Errors
+    /// Retourne [`FsError::OutOfBounds`] si l’emplacement est invalide.
+    fn touch_mtime(&mut self, loc: &EntryLocation) -> Result<(), FsError> {
+        let now = self.now();
+        let mut entry = FatDir::new(&self.read_entry_at(loc.cluster, loc.offset_in_cluster)?);
+
+        entry.write_time = now.to_fat_time();
+        entry.write_date = now.to_fat_date();
+        entry.access_date = now.to_fat_date();
+
+        self.write_entry_at(loc.cluster, loc.offset_in_cluster, &entry.to_bytes())
     }
 
     /// Lit un secteur logique du disque.
     ///
     /// # Panics
-    /// Panique si l’adresse dépasse la taille du disque.
+    /// Panique si l’adresse dépasse la taille du disque (voir [`Self::try_read_sector`]).
     pub fn read_sector(&self, address: u32) -> Vec<u8> {
-        let offset = (address * self.bytes_per_sector) as usize;
-        let size = self.bytes_per_sector as usize;
+        self.try_read_sector(address)
+            .expect("Error reading outbound")
+    }
+
+    /// Lit un secteur logique du disque sans paniquer.
+    ///
+    /// Lorsque [`MountOptions::allow_short_reads`] est activé, la partie du secteur située
+    /// au-delà de la fin de l’image est complétée par des zéros.
+    ///
+    /// # Errors
+    /// - [`FsError::OutOfBounds`] si le secteur dépasse la taille du disque
+    ///   et que les lectures tronquées ne sont pas autorisées
+    /// - [`FsError::BadSector`] ou toute autre erreur retournée par le [`ReadHook`]
+    ///   du périphérique (voir [`Self::set_read_hook`])
+    pub fn try_read_sector(&self, address: u32) -> Result<Vec<u8>, FsError> {
+        self.read_sectors(address, 1)
+    }
+
+    /// Lit `count` secteurs consécutifs en une seule lecture de blocs.
+    ///
+    /// La plage est vérifiée puis copiée en une seule allocation. Les lectures
+    /// tronquées suivent la même règle que [`Self::try_read_sector`].
+    ///
+    /// # Errors
+    /// Mêmes conditions que [`Self::try_read_sector`], pour chaque secteur de la plage.
+    pub fn read_sectors(&self, start: u32, count: u32) -> Result<Vec<u8>, FsError> {
+        let mut data = vec![0u8; (count * self.bytes_per_sector) as usize];
+        self.read_sectors_into(start, &mut data)?;
+        Ok(data)
+    }
+
+    /// Remplit `buf` avec les secteurs consécutifs débutant à `start`, sans allocation.
+    ///
+    /// La longueur de `buf` détermine la taille lue ; les règles de [`Self::read_sectors`]
+    /// s’appliquent.
+    fn read_sectors_into(&self, start: u32, buf: &mut [u8]) -> Result<(), FsError> {
+        let offset = (start * self.bytes_per_sector) as usize;
+        let size = buf.len();
+
+        if let Some(hook) = &self.read_hook {
+            hook.before_read(start, size.div_ceil(self.bytes_per_sector as usize) as u32)?;
+        }
+
+        if offset + size <= self.disk.len() {
+            buf.copy_from_slice(&self.disk[offset..offset + size]);
+            return Ok(());
+        }
+
+        if !self.options.allow_short_reads {
+            return Err(FsError::OutOfBounds);
+        }
+
+        buf.fill(0);
+        if offset < self.disk.len() {
+            let available = self.disk.len() - offset;
+            buf[..available].copy_from_slice(&self.disk[offset..]);
+        }
+
+        Ok(())
+    }
+
+    /// Lit un cluster complet (tous ses secteurs).
+    pub fn read_cluster(&self, cluster_id: u32) -> Vec<u8> {
+        let mut data = vec![0u8; (self.sectors_per_cluster * self.bytes_per_sector) as usize];
+        self.read_cluster_into(cluster_id, &mut data)
+            .expect("Error reading outbound");
+        data
+    }
+
+    /// Copie un cluster complet dans un tampon fourni par l’appelant, sans allocation.
+    ///
+    /// Seuls les `taille_cluster` premiers octets de `buf` sont écrits.
+    ///
+    /// # Errors
+    /// - [`FsError::OutOfBounds`] si `buf` est plus petit qu’un cluster
+    /// - Erreurs de [`Self::cluster_to_sector`] et [`Self::read_sectors`]
+    pub fn read_cluster_into(&self, cluster_id: u32, buf: &mut [u8]) -> Result<(), FsError> {
+        let cluster_bytes = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
+        let buf = buf.get_mut(..cluster_bytes).ok_or(FsError::OutOfBounds)?;
+
+        let start = self.cluster_to_sector(cluster_id)?;
+        self.read_sectors_into(start, buf)
+    }
+
+    /// Lit `count` clusters contigus en une seule lecture de blocs.
+    fn read_cluster_run(&self, first_cluster: u32, count: u32) -> Vec<u8> {
+        self.cluster_to_sector(first_cluster)
+            .and_then(|start| self.read_sectors(start, count * self.sectors_per_cluster))
+            .expect("Error reading outbound")
+    }
+
+    /// Retourne le premier secteur d’un cluster de la zone de données.
+    ///
+    /// Toutes les lectures et écritures de clusters passent par cette conversion.
+    ///
+    /// # Errors
+    /// - [`FsError::InvalidCluster`] si `cluster_id` ne vérifie pas [`Self::is_valid_cluster`]
+    /// - [`FsError::OutOfBounds`] si le numéro de secteur dépasse `u32`
+    pub fn cluster_to_sector(&self, cluster_id: u32) -> Result<u32, FsError> {
+        if !self.is_valid_cluster(cluster_id) {
+            return Err(FsError::InvalidCluster);
+        }
+
+        cluster_id
+            .checked_sub(2)
+            .and_then(|index| index.checked_mul(self.sectors_per_cluster))
+            .and_then(|offset| offset.checked_add(self.data_sector))
+            .ok_or(FsError::OutOfBounds)
+    }
+
+    /// Lit une entrée FAT pour obtenir le cluster suivant.
+    ///
+    /// Les bits de poids fort sont masqués conformément à la spécification FAT32.
+    /// Si le secteur de la FAT principale est illisible, les copies miroirs sont essayées.
+    ///
+    /// # Panics
+    /// Panique si l’entrée est illisible dans toutes les copies de la FAT.
+    fn read_fat_entry(&self, cluster_id: u32) -> u32 {
+        (0..self.num_fats.max(1))
+            .find_map(|copy| self.read_fat_entry_from(copy, cluster_id).ok())
+            .expect("Error reading outbound")
+    }
+
+    /// Lit une entrée de la FAT principale en réutilisant le secteur conservé dans `cache`.
+    ///
+    /// Le secteur n’est relu que si l’entrée se trouve dans un autre secteur. En cas
+    /// d’échec de lecture, [`Self::read_fat_entry`] et ses copies miroirs prennent le relais.
+    fn read_fat_entry_cached(&self, cluster_id: u32, cache: &mut Option<(u32, Vec<u8>)>) -> u32 {
+        let fat_offset = cluster_id * 4;
+        let sector_num = self.fat_sector + fat_offset / self.bytes_per_sector;
+        let index = (fat_offset % self.bytes_per_sector) as usize;
+
+        match cache {
+            Some((cached, _)) if *cached == sector_num => {}
+            _ => match self.try_read_sector(sector_num) {
+                Ok(sector) => *cache = Some((sector_num, sector)),
+                Err(_) => return self.read_fat_entry(cluster_id),
+            },
+        }
+
+        cache
+            .as_ref()
+            .and_then(|(_, sector)| sector.get(index..index + 4))
+            .map(|entry| u32::from_le_bytes(entry.try_into().unwrap()) & FAT_ENTRY_MASK)
+            .unwrap_or_else(|| self.read_fat_entry(cluster_id))
+    }
+
+    /// Lit une entrée dans la copie `copy` de la FAT (0 pour la FAT principale).
+    ///
+    /// # Errors
+    /// - [`FsError::OutOfBounds`] si la copie n’existe pas ou si son secteur est hors du disque
+    /// - [`FsError::BadSector`] si son secteur est illisible
+    /// - [`FsError::BadGeometry`] si l’entrée chevauche deux secteurs
+    pub fn read_fat_entry_from(&self, copy: u32, cluster_id: u32) -> Result<u32, FsError> {
+        if copy >= self.num_fats.max(1) {
+            return Err(FsError::OutOfBounds);
+        }
+
+        let fat_offset = cluster_id * 4;
+        let fat_sector =
+            self.fat_sector + copy * self.sectors_per_fat + fat_offset / self.bytes_per_sector;
+        let fat_index = (fat_offset % self.bytes_per_sector) as usize;
+        let sector = self.try_read_sector(fat_sector)?;
+
+        if fat_index + 4 > sector.len() {
+            return Err(FsError::BadGeometry);
+        }
+
+        let entry = u32::from_le_bytes(sector[fat_index..fat_index + 4].try_into().unwrap());
+        Ok(entry & FAT_ENTRY_MASK)
+    }
+
+    /// Calcule le CRC32 (IEEE 802.3) de toute l’image disque.
+    ///
+    /// Permet de vérifier qu’une opération en lecture seule ne modifie pas le disque.
+    pub fn checksum(&self) -> u32 {
+        !self.disk.iter().fold(0xFFFFFFFF, |crc, &byte| {
+            CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+        })
+    }
+
+    /// Retourne le nombre de clusters de la zone de données adressables par la FAT.
+    pub fn cluster_count(&self) -> u32 {
+        let total_sectors = self.disk.len() as u32 / self.bytes_per_sector;
+        let data_clusters =
+            total_sectors.saturating_sub(self.data_sector) / self.sectors_per_cluster;
+        let fat_entries = self.sectors_per_fat * self.bytes_per_sector / 4;
+
+        data_clusters.min(fat_entries.saturating_sub(2))
+    }
+
+    /// Indique si `cluster` désigne un cluster de la zone de données (`2..=cluster_count + 1`).
+    ///
+    /// Si [`MountOptions::allow_short_reads`] est activé, les clusters adressables par la FAT mais
+    /// absents d’une image tronquée restent valides (ils sont lus comme des zéros).
+    pub fn is_valid_cluster(&self, cluster: u32) -> bool {
+        let count = if self.options.allow_short_reads {
+            (self.sectors_per_fat * self.bytes_per_sector / 4).saturating_sub(2)
+        } else {
+            self.cluster_count()
+        };
+
+        cluster >= 2 && cluster - 2 < count
+    }
+
+    /// Lit toutes les entrées de la FAT principale (index 0 et 1 compris) en une seule lecture.
+    ///
+    /// Si la FAT principale est illisible, la première copie miroir lisible est utilisée.
+    fn fat_entries(&self) -> Vec<u32> {
+        let fat = (0..self.num_fats.max(1))
+            .find_map(|copy| {
+                self.read_sectors(
+                    self.fat_sector + copy * self.sectors_per_fat,
+                    self.sectors_per_fat,
+                )
+                .ok()
+            })
+            .expect("Error reading outbound");
+
+        fat.chunks_exact(4)
+            .take(self.cluster_count() as usize + 2)
+            .map(|entry| u32::from_le_bytes(entry.try_into().unwrap()) & FAT_ENTRY_MASK)
+            .collect()
+    }
+
+    /// Compare chaque copie miroir de la FAT à la FAT principale.
+    ///
+    /// Retourne, pour chaque copie divergente, son index et le premier cluster
+    /// dont l’entrée diffère.
+    pub fn verify_fats(&self) -> Vec<(u32, u32)> {
+        let primary = self.read_sectors(self.fat_sector, self.sectors_per_fat);
+
+        (1..self.num_fats)
+            .filter_map(|copy| {
+                let mirror = self.read_sectors(
+                    self.fat_sector + copy * self.sectors_per_fat,
+                    self.sectors_per_fat,
+                );
+
+                match (&primary, &mirror) {
+                    (Ok(primary), Ok(mirror)) => primary
+                        .chunks_exact(4)
+                        .zip(mirror.chunks_exact(4))
+                        .position(|(a, b)| a != b)
+                        .map(|cluster| (copy, cluster as u32)),
+                    _ => Some((copy, 0)),
+                }
+            })
+            .collect()
+    }
+
+    /// Recalcule le nombre de clusters libres en parcourant la FAT.
+    pub fn recompute_free_count(&self) -> u32 {
+        self.iter_free_clusters().count() as u32
+    }
+
+    /// Retourne le nombre de clusters libres sans parcourir la FAT à chaque appel.
+    ///
+    /// Le compteur est calculé par [`Self::recompute_free_count`] au premier appel (le
+    /// compteur du FSInfo, souvent obsolète, n’est pas repris), puis ajusté à chaque
+    /// écriture dans la FAT : le coût reste constant, quelle que soit la taille du volume.
+    /// Après une modification directe de [`Self::disk`], appeler
+    /// [`Self::invalidate_free_count`].
+    pub fn free_count_cached(&self) -> u32 {
+        match self.free_count.get() {
+            Some(count) => count,
+            None => {
+                let count = self.recompute_free_count();
+                self.free_count.set(Some(count));
+                count
+            }
+        }
+    }
+
+    /// Oublie le compteur de clusters libres : le prochain appel de
+    /// [`Self::free_count_cached`] parcourt de nouveau la FAT.
+    pub fn invalidate_free_count(&self) {
+        self.free_count.set(None);
+    }
+
+    /// Énumère, dans l’ordre croissant, les clusters libres de la FAT principale.
+    ///
+    /// La FAT est lue en une seule fois ; utile pour visualiser la fragmentation de
+    /// l’espace libre et vérifier l’allocateur.
+    pub fn iter_free_clusters(&self) -> impl Iterator<Item = u32> {
+        self.fat_entries()
+            .into_iter()
+            .enumerate()
+            .skip(2)
+            .filter(|&(_, entry)| entry == FAT_FREE)
+            .map(|(cluster, _)| cluster as u32)
+    }
+
+    /// Retourne la taille d’un cluster en octets.
+    fn cluster_bytes(&self) -> u64 {
+        self.sectors_per_cluster as u64 * self.bytes_per_sector as u64
+    }
+
+    /// Retourne la capacité de la zone de données en octets (clusters × taille de cluster).
+    pub fn total_size_bytes(&self) -> u64 {
+        self.cluster_count() as u64 * self.cluster_bytes()
+    }
+
+    /// Retourne l’espace libre en octets, d’après les clusters libres de la FAT
+    /// (voir [`Self::free_count_cached`]).
+    pub fn free_size_bytes(&self) -> u64 {
+        self.free_count_cached() as u64 * self.cluster_bytes()
+    }
+
+    /// Retourne l’espace occupé en octets (capacité moins espace libre).
+    pub fn used_size_bytes(&self) -> u64 {
+        self.total_size_bytes() - self.free_size_bytes()
+    }
+
+    /// Lit le compteur de clusters libres enregistré dans le FSInfo.
+    ///
+    /// Retourne `None` si le volume n’a pas de FSInfo, si ses signatures sont invalides
+    /// ou si le compteur est inconnu (`0xFFFFFFFF`).
+    pub fn fsinfo_free_count(&self) -> Option<u32> {
+        self.read_fsinfo_field(488)
+    }
+
+    /// Lit l’indice du prochain cluster libre enregistré dans le FSInfo.
+    ///
+    /// Retourne `None` dans les mêmes cas que [`Self::fsinfo_free_count`].
+    pub fn fsinfo_next_free(&self) -> Option<u32> {
+        self.read_fsinfo_field(492)
+    }
+
+    /// Compare le BPB du secteur de boot de secours à celui du secteur principal.
+    ///
+    /// Les 90 premiers octets (saut, nom OEM et BPB FAT32) sont comparés.
+    /// Un volume sans copie de secours est considéré valide.
+    ///
+    /// # Errors
+    /// - [`FsError::BootMismatch`] si les deux BPB diffèrent
+    /// - [`FsError::OutOfBounds`] si un des secteurs est illisible
+    pub fn verify_boot_backup(&self) -> Result<(), FsError> {
+        const BPB_LEN: usize = 90;
+
+        if self.backup_boot_sector == 0 {
+            return Ok(());
+        }
+
+        let primary = self.try_read_sector(0)?;
+        let backup = self.try_read_sector(self.backup_boot_sector)?;
+
+        if primary[..BPB_LEN] != backup[..BPB_LEN] {
+            return Err(FsError::BootMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Lit un champ 32 bits du FSInfo après vérification de ses signatures.
+    fn read_fsinfo_field(&self, offset: usize) -> Option<u32> {
+        if self.fsinfo_sector == 0 {
+            return None;
+        }
+
+        let sector = self.try_read_sector(self.fsinfo_sector).ok()?;
+        let read =
+            |offset: usize| u32::from_le_bytes(sector[offset..offset + 4].try_into().unwrap());
+
+        if read(0) != 0x41615252 || read(484) != 0x61417272 {
+            return None;
+        }
+
+        Some(read(offset)).filter(|&value| value != 0xFFFFFFFF)
+    }
+
+    /// Écrit un champ de 32 bits du FSInfo, si le volume en possède un valide.
+    ///
+    /// Maintient le compteur de clusters libres (offset 488) et le prochain cluster libre
+    /// (offset 492) en phase avec la FAT, pour que [`Self::check`] reste propre après une
+    /// écriture. Le FSInfo de la copie de secours n’est pas modifié.
+    fn write_fsinfo_field(&mut self, offset: usize, value: u32) {
+        if self.fsinfo_sector == 0 {
+            return;
+        }
+
+        let start = (self.fsinfo_sector * self.bytes_per_sector) as usize;
+        let Some(sector) = self.disk.get_mut(start..start + 512) else {
+            return;
+        };
+        let read = |sector: &[u8], offset: usize| {
+            u32::from_le_bytes(sector[offset..offset + 4].try_into().unwrap())
+        };
+
+        if read(sector, 0) == 0x41615252 && read(sector, 484) == 0x61417272 {
+            sector[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    /// Lit le contenu d’un fichier texte à partir de son chemin.
+    ///
+    /// - Supporte les chemins absolus et relatifs
+    /// - Gère les chaînes de clusters FAT
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`]
+    /// - [`FsError::NotAFile`]
+    /// - [`FsError::InvalidUtf8`]
+    pub fn read_file(&self, path: &str, current_cluster: Option<u32>) -> Result<String, FsError> {
+        let file = self.resolve(path, current_cluster)?.file;
+
+        let data = self.read_file_info(&file)?;
+        String::from_utf8(data).map_err(|_| FsError::InvalidUtf8)
+    }
+
+    /// Lit le contenu brut d’un fichier à partir de son chemin.
+    ///
+    /// Contrairement à [`Self::read_file`], le contenu n’a pas à être de l’UTF-8.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`]
+    /// - [`FsError::NotAFile`]
+    pub fn read_file_bytes(
+        &self,
+        path: &str,
+        current_cluster: Option<u32>,
+    ) -> Result<Vec<u8>, FsError> {
+        let file = self.resolve(path, current_cluster)?.file;
+
+        self.read_file_info(&file)
+    }
+
+    /// Lit au plus les `n` premiers clusters d’un fichier (aperçu d’un gros fichier).
+    ///
+    /// La chaîne n’est suivie que sur `n` clusters ; le résultat est tronqué à la taille
+    /// du fichier s’il est plus court.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`]
+    /// - [`FsError::NotAFile`] si le chemin désigne un répertoire
+    /// - [`FsError::FileTooLarge`] si l’aperçu dépasse [`MountOptions::max_read_bytes`]
+    pub fn read_file_first_clusters(&self, path: &str, n: u32) -> Result<Vec<u8>, FsError> {
+        let file = self.resolve(path, None)?.file;
+        if file.is_directory {
+            return Err(FsError::NotAFile);
+        }
+
+        let cluster_bytes = self.cluster_bytes();
+        let length = (file.size as u64).min(n as u64 * cluster_bytes) as usize;
+        if length > self.options.max_read_bytes {
+            return Err(FsError::FileTooLarge);
+        }
+
+        let mut data = Vec::with_capacity(length);
+        let mut cluster = file.start_cluster;
+        let mut cache = None;
+
+        while data.len() < length && self.is_valid_cluster(cluster) {
+            data.extend(self.read_cluster(cluster));
+
+            let next = self.read_fat_entry_cached(cluster, &mut cache);
+            if is_end_of_chain(next) {
+                break;
+            }
+            cluster = next;
+        }
+
+        data.truncate(length);
+        Ok(data)
+    }
+
+    /// Retourne l’entrée désignée par un chemin (taille, cluster de départ, horodatages).
+    ///
+    /// # Errors
+    /// Erreurs de [`Self::resolve`]
+    pub fn metadata(&self, path: &str, current_cluster: Option<u32>) -> Result<FileInfo, FsError> {
+        Ok(self.resolve(path, current_cluster)?.file)
+    }
+
+    /// Lit un fichier et retourne son entrée avec son contenu, en une seule résolution.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`]
+    /// - Erreurs de [`Self::read_file_info`]
+    pub fn read_file_with_meta(
+        &self,
+        path: &str,
+        current_cluster: Option<u32>,
+    ) -> Result<(FileInfo, Vec<u8>), FsError> {
+        let file = self.resolve(path, current_cluster)?.file;
+        let data = self.read_file_info(&file)?;
+
+        Ok((file, data))
+    }
+
+    /// Lit le début d’un fichier comme texte, pour un aperçu (`cat --preview`).
+    ///
+    /// Au plus `max_bytes` octets sont lus (seuls les clusters nécessaires sont parcourus,
+    /// voir [`Self::file_clusters`]), puis le plus long préfixe UTF-8 valide est retourné :
+    /// un fichier binaire donne le texte qui précède le premier octet invalide.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`]
+    /// - [`FsError::NotAFile`] si le chemin désigne un répertoire
+    /// - [`FsError::FileTooLarge`] si l’aperçu dépasse [`MountOptions::max_read_bytes`]
+    /// - Erreurs de lecture de [`Self::file_clusters`]
+    pub fn read_text_prefix(&self, path: &str, max_bytes: usize) -> Result<String, FsError> {
+        let file = self.resolve(path, None)?.file;
+
+        if file.is_directory {
+            return Err(FsError::NotAFile);
+        }
+
+        let length = (file.size as usize).min(max_bytes);
+        if length > self.options.max_read_bytes {
+            return Err(FsError::FileTooLarge);
+        }
+
+        let mut data = Vec::with_capacity(length);
+        for cluster in self.cluster_reader(&file) {
+            if data.len() == length {
+                break;
+            }
+            let cluster = cluster?;
+            let count = (length - data.len()).min(cluster.len());
+            data.extend_from_slice(&cluster[..count]);
+        }
+
+        let valid = match core::str::from_utf8(&data) {
+            Ok(text) => text,
+            Err(error) => core::str::from_utf8(&data[..error.valid_up_to()]).unwrap(),
+        };
+
+        Ok(valid.to_string())
+    }
+
+    /// Parcourt le contenu d’un fichier cluster par cluster, sans le charger en entier.
+    ///
+    /// Chaque cluster est lu avec [`Self::read_sectors`] ; le dernier est tronqué à la
+    /// longueur restante du fichier. Une lecture en échec est retournée comme élément,
+    /// puis l’itération s’arrête.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`]
+    /// - [`FsError::NotAFile`] si le chemin désigne un répertoire
+    pub fn file_clusters(&self, path: &str) -> Result<ClusterReader<'_>, FsError> {
+        let file = self.resolve(path, None)?.file;
+
+        if file.is_directory {
+            return Err(FsError::NotAFile);
+        }
+
+        Ok(self.cluster_reader(&file))
+    }
+
+    /// Itérateur sur les clusters d’un fichier déjà résolu (voir [`Self::file_clusters`]).
+    fn cluster_reader(&self, file: &FileInfo) -> ClusterReader<'_> {
+        ClusterReader {
+            fs: self,
+            cluster: file.start_cluster,
+            visited: BTreeSet::new(),
+            fat_cache: None,
+            remaining: file.size as usize,
+        }
+    }
+
+    /// Parcourt les slots bruts de 32 octets de toute la chaîne d’un répertoire, avec leur
+    /// emplacement `(cluster, offset dans le cluster)`.
+    ///
+    /// Aucun slot n’est interprété : fragments LFN, entrées supprimées (`0xE5`) et slots
+    /// libres au-delà du marqueur de fin (`0x00`) sont tous retournés, ce qui expose les
+    /// octets exacts (horodatages, octet réservé `NTRes`…) aux outils d’analyse. Chaque
+    /// cluster n’est lu qu’une fois atteint.
+    pub fn iter_dir_raw(&self, cluster: u32) -> impl Iterator<Item = ([u8; 32], u32, usize)> {
+        self.cluster_chain(cluster)
+            .into_iter()
+            .flat_map(move |cluster| {
+                let data = self.read_cluster(cluster);
+                (0..data.len() / 32).map(move |idx| {
+                    let entry: [u8; 32] = data[idx * 32..idx * 32 + 32].try_into().unwrap();
+                    (entry, cluster, idx * 32)
+                })
+            })
+    }
+
+    /// Parcourt paresseusement les entrées du répertoire débutant à `cluster`.
+    ///
+    /// Contrairement à [`list_directory_entries`], aucune liste n’est construite : la
+    /// mémoire utilisée reste bornée à un cluster, quelle que soit la taille du répertoire.
+    pub fn dir_iter(&self, cluster: u32) -> DirIter<'_> {
+        DirIter {
+            slots: DirSlots::new(self, cluster, false),
+        }
+    }
+
+    /// Résout une fois pour toutes un répertoire et retourne un handle réutilisable.
+    ///
+    /// Les appels suivants (listing, recherche d’un nom) repartent du cluster mémorisé au
+    /// lieu de résoudre à nouveau le chemin. `/` et le chemin vide désignent la racine.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`]
+    /// - [`FsError::NotADirectory`] si le chemin désigne un fichier
+    pub fn open_dir(
+        &self,
+        path: &str,
+        current_cluster: Option<u32>,
+    ) -> Result<DirHandle<'_>, FsError> {
+        let cluster = if path.trim_matches('/').is_empty() {
+            if path.starts_with('/') {
+                self.root_cluster
+            } else {
+                current_cluster.unwrap_or(self.root_cluster)
+            }
+        } else {
+            let dir = self.resolve(path, current_cluster)?.file;
+            if !dir.is_directory {
+                return Err(FsError::NotADirectory);
+            }
+            self.dir_cluster(&dir)
+        };
+
+        Ok(DirHandle { fs: self, cluster })
+    }
+
+    /// Lit le contenu brut d’un fichier déjà résolu.
+    ///
+    /// Évite une seconde résolution de chemin lorsque l’appelant dispose déjà du [`FileInfo`].
+    /// Les clusters contigus sont lus par groupes de [`MountOptions::readahead_clusters`].
+    /// Voir [`MountOptions::clamp_size_to_chain`] pour les tailles incohérentes avec la chaîne.
+    ///
+    /// # Errors
+    /// - [`FsError::NotAFile`] si l’entrée est un répertoire
+    /// - [`FsError::InvalidCluster`] si un fichier non vide ne pointe pas sur un cluster de données
+    /// - [`FsError::FileTooLarge`] si les octets lus dépassent [`MountOptions::max_read_bytes`]
+    ///   (vérifié avant toute lecture) : toute la chaîne, ou `min(taille, longueur de la
+    ///   chaîne)` avec [`MountOptions::clamp_size_to_chain`]
+    pub fn read_file_info(&self, file: &FileInfo) -> Result<Vec<u8>, FsError> {
+        if file.is_directory {
+            return Err(FsError::NotAFile);
+        }
+        // Un fichier vide n’a en principe aucun cluster (`start_cluster == 0`)
+        if file.size == 0 {
+            return Ok(Vec::new());
+        }
+        if !self.is_valid_cluster(file.start_cluster) {
+            return Err(FsError::InvalidCluster);
+        }
+
+        let mut chain = self.cluster_chain(file.start_cluster);
+        let cluster_bytes = self.cluster_bytes();
+        let chain_bytes = chain.len() as u64 * cluster_bytes;
+        let length = (file.size as u64).min(chain_bytes);
+
+        // Sans bornage, toute la chaîne est lue et mise en mémoire avant la troncature
+        let buffered = if self.options.clamp_size_to_chain {
+            chain.truncate(length.div_ceil(cluster_bytes) as usize);
+            length
+        } else {
+            chain_bytes
+        };
+        if buffered > self.options.max_read_bytes as u64 {
+            return Err(FsError::FileTooLarge);
+        }
+
+        let max_run = self.options.readahead_clusters.max(1) as usize;
+        let mut data = Vec::with_capacity(chain.len() * cluster_bytes as usize);
+        let mut i = 0;
+
+        // Regroupe les clusters contigus de la chaîne en lectures multi-clusters
+        while i < chain.len() {
+            let mut run = 1;
+            while run < max_run && i + run < chain.len() && chain[i + run] == chain[i] + run as u32
+            {
+                run += 1;
+            }
+
+            data.extend(self.read_cluster_run(chain[i], run as u32));
+            i += run;
+        }
+
+        data.truncate(file.size as usize);
+        Ok(data)
+    }
+
+    /// Liste le contenu d’un répertoire déjà résolu.
+    ///
+    /// Retourne une liste vide si l’entrée n’est pas un répertoire.
+    pub fn list_dir_info(&self, file: &FileInfo) -> Vec<FileInfo> {
+        if !file.is_directory {
+            return Vec::new();
+        }
+
+        list_directory_entries(self, file.start_cluster)
+    }
+
+    /// Liste le contenu du répertoire racine.
+    pub fn root_entries(&self) -> Vec<FileInfo> {
+        list_directory_entries(self, self.root_cluster)
+    }
+
+    /// Retourne une entrée synthétique (`/`) désignant le répertoire racine.
+    ///
+    /// Utilisable avec [`Self::list_dir_info`] comme n’importe quel répertoire résolu.
+    pub fn root_info(&self) -> FileInfo {
+        FileInfo::new("/".to_string(), true, 0, self.root_cluster)
+    }
+
+    /// Résout un chemin en parcourant récursivement les répertoires.
+    fn parse_path(&self, path: &str, current_cluster: Option<u32>) -> Option<FileInfo> {
+        self.resolve(path, current_cluster).ok().map(|r| r.file)
+    }
+
+    /// Résout un chemin et retourne l’entrée trouvée, le cluster de son répertoire parent
+    /// et les composantes du chemin parcourues.
+    ///
+    /// Les composantes `.` sont ignorées et `..` retire la composante précédente lorsque
+    /// c’est possible ; à la racine, `..` désigne la racine elle-même.
+    ///
+    /// # Errors
+    /// - [`FsError::NotFound`] si une composante est introuvable
+    /// - [`FsError::NotADirectory`] si une composante intermédiaire est un fichier, ou si
+    ///   le chemin se termine par `/` et désigne un fichier
+    /// - [`FsError::CorruptChain`] si une composante est introuvable dans un répertoire
+    ///   dont la chaîne de clusters est cyclique
+    pub fn resolve(
+        &self,
+        path: &str,
+        current_cluster: Option<u32>,
+    ) -> Result<ResolvedPath, FsError> {
+        self.resolve_components(path, current_cluster)
+            .map_err(|(error, _)| error)
+    }
+
+    /// Résout un chemin en indiquant, en cas d’échec, les composantes déjà résolues.
+    ///
+    /// Permet d’afficher un message précis (`no such directory: b (in /a)`).
+    ///
+    /// # Errors
+    /// Retourne l’erreur de [`Self::resolve`] accompagnée des composantes résolues
+    /// avant l’échec.
+    pub fn resolve_verbose(
+        &self,
+        path: &str,
+        current_cluster: Option<u32>,
+    ) -> Result<FileInfo, (FsError, Vec<String>)> {
+        self.resolve_components(path, current_cluster)
+            .map(|resolved| resolved.file)
+    }
+
+    /// Implémentation commune de [`Self::resolve`] et [`Self::resolve_verbose`].
+    fn resolve_components(
+        &self,
+        path: &str,
+        current_cluster: Option<u32>,
+    ) -> Result<ResolvedPath, (FsError, Vec<String>)> {
+        let mut cluster = if path.starts_with("/") || path.is_empty() {
+            self.root_cluster
+        } else {
+            current_cluster.unwrap_or(self.root_cluster)
+        };
+
+        let parts: Vec<&str> = path.split("/").filter(|s| !s.is_empty()).collect();
+        let must_be_directory = path.ends_with('/');
+        let mut components: Vec<String> = Vec::new();
+
+        for (i, part) in parts.iter().enumerate() {
+            let is_last = i == parts.len() - 1;
+
+            match *part {
+                "." => continue,
+                // Comme dans la plupart des shells, `..` à la racine reste à la racine
+                ".." if cluster == self.root_cluster => {
+                    if is_last {
+                        return Ok(ResolvedPath {
+                            file: FileInfo::new("..".to_string(), true, 0, cluster),
+                            parent_cluster: cluster,
+                            components,
+                        });
+                    }
+                    continue;
+                }
+                ".." => {
+                    let parent = self
+                        .find_parent_cluster(cluster)
+                        .ok_or_else(|| (FsError::NotFound, components.clone()))?;
+
+                    match components.last() {
+                        Some(last) if last != ".." => {
+                            components.pop();
+                        }
+                        _ => components.push("..".to_string()),
+                    }
+
+                    if is_last {
+                        return Ok(ResolvedPath {
+                            file: FileInfo::new("..".to_string(), true, 0, parent),
+                            parent_cluster: cluster,
+                            components,
+                        });
+                    }
+
+                    cluster = parent;
+                    continue;
+                }
+                _ => {}
+            }
+
+            // Une entrée absente d’un répertoire cyclique n’est pas signalée comme introuvable
+            let mut entries = self.dir_iter(cluster);
+            let Some(file) = entries.find(|f| f.name == *part) else {
+                let error = entries.check_chain().err().unwrap_or(FsError::NotFound);
+                return Err((error, components));
+            };
+            components.push(part.to_string());
+
+            if file.is_directory {
+                self.check_dot_entry(file.start_cluster)
+                    .map_err(|error| (error, components.clone()))?;
+            }
+
+            if is_last {
+                // Comme en POSIX, un `/` final impose un répertoire
+                if must_be_directory && !file.is_directory {
+                    return Err((FsError::NotADirectory, components));
+                }
+
+                return Ok(ResolvedPath {
+                    file,
+                    parent_cluster: cluster,
+                    components,
+                });
+            }
+
+            if !file.is_directory {
+                return Err((FsError::NotADirectory, components));
+            }
+
+            cluster = file.start_cluster;
+        }
+
+        Err((FsError::NotFound, components))
+    }
+
+    /// Vérifie que l’entrée `.` du répertoire pointe sur `dir_cluster`
+    /// (uniquement si [`MountOptions::strict_dot_entries`] est activé).
+    ///
+    /// # Errors
+    /// Retourne [`FsError::CorruptEntry`] si l’entrée `.` est absente ou pointe ailleurs.
+    fn check_dot_entry(&self, dir_cluster: u32) -> Result<(), FsError> {
+        if !self.options.strict_dot_entries || dir_cluster == self.root_cluster {
+            return Ok(());
+        }
+
+        match list_directory_entries(self, dir_cluster)
+            .iter()
+            .find(|f| f.is_dot_entry && f.name == ".")
+        {
+            Some(dot) if dot.start_cluster == dir_cluster => Ok(()),
+            _ => Err(FsError::CorruptEntry),
+        }
+    }
+
+    /// Recherche le cluster parent d’un répertoire via l’entrée `..`.
+    fn find_parent_cluster(&self, current_cluster: u32) -> Option<u32> {
+        if current_cluster == self.root_cluster {
+            return None;
+        }
+
+        let files = list_directory_entries(self, current_cluster);
+        let parent = files.iter().find(|f| f.is_dot_entry && f.name == "..")?;
+
+        Some(parent.start_cluster)
+    }
+
+    /// Retourne la liste des clusters composant la chaîne qui débute à `start_cluster`.
+    ///
+    /// Le parcours s’arrête sur un marqueur de fin de chaîne, sur un cluster libre ou
+    /// invalide (voir [`Self::is_valid_cluster`]), ou sur un cluster déjà visité (chaîne cyclique).
+    /// Le dernier secteur de FAT lu est conservé : une chaîne contiguë ne coûte qu’une
+    /// lecture par secteur de FAT (128 entrées pour des secteurs de 512 octets).
+    pub fn cluster_chain(&self, start_cluster: u32) -> Vec<u32> {
+        self.follow_chain(start_cluster).0
+    }
+
+    /// Variante de [`Self::cluster_chain`] qui signale une chaîne cyclique au lieu de la tronquer.
+    ///
+    /// # Errors
+    /// - [`FsError::CorruptChain`] si la chaîne repasse par un cluster déjà visité
+    pub fn checked_cluster_chain(&self, start_cluster: u32) -> Result<Vec<u32>, FsError> {
+        match self.follow_chain(start_cluster) {
+            (chain, false) => Ok(chain),
+            (_, true) => Err(FsError::CorruptChain),
+        }
+    }
+
+    /// Parcourt une chaîne de clusters ; le booléen indique un arrêt sur un cluster déjà visité.
+    fn follow_chain(&self, start_cluster: u32) -> (Vec<u32>, bool) {
+        let mut chain = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut cluster = start_cluster;
+        let mut cache = None;
+
+        while self.is_valid_cluster(cluster) {
+            if !visited.insert(cluster) {
+                return (chain, true);
+            }
+            chain.push(cluster);
+
+            let next = self.read_fat_entry_cached(cluster, &mut cache);
+            if is_end_of_chain(next) {
+                break;
+            }
+            cluster = next;
+        }
+
+        (chain, false)
+    }
+
+    /// Vérifie que la chaîne de clusters d’un fichier correspond à sa taille déclarée.
+    ///
+    /// Un fichier de `size` octets doit occuper `ceil(size / taille_cluster)` clusters.
+    /// Un fichier vide pointant vers un cluster (fichier fraîchement créé) en occupe un.
+    ///
+    /// # Errors
+    /// - [`FsError::NotFound`] / [`FsError::NotAFile`] si le chemin est invalide
+    /// - [`FsError::SizeMismatch`] si la chaîne est plus longue ou plus courte que prévu
+    pub fn verify_file(&self, path: &str) -> Result<(), FsError> {
+        let file = self.parse_path(path, None).ok_or(FsError::NotFound)?;
+
+        if file.is_directory {
+            return Err(FsError::NotAFile);
+        }
+
+        let cluster_size = self.sectors_per_cluster * self.bytes_per_sector;
+        let expected = match file.size.div_ceil(cluster_size) {
+            0 if file.start_cluster != 0 => 1,
+            count => count,
+        };
+        let actual = self.cluster_chain(file.start_cluster).len() as u32;
+
+        if expected != actual {
+            return Err(FsError::SizeMismatch { expected, actual });
+        }
+
+        Ok(())
+    }
+
+    /// Corrige la taille d’un fichier d’après la longueur de sa chaîne de clusters et
+    /// retourne la nouvelle taille (réparation ciblée de [`FsError::SizeMismatch`]).
+    ///
+    /// Une taille cohérente avec la chaîne (voir [`Self::verify_file`]) n’est pas modifiée.
+    /// Une taille dépassant la capacité de la chaîne y est ramenée. Une taille trop petite
+    /// (données masquées) devient la capacité de la chaîne si `assume_full_last` est
+    /// activé ; sinon, les octets nuls de fin du dernier cluster sont exclus.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::locate_entry`]
+    /// - [`FsError::NotAFile`] si le chemin désigne un répertoire
+    /// - [`FsError::CorruptChain`] si la chaîne est cyclique
+    pub fn sync_size_from_chain(
+        &mut self,
+        path: &str,
+        assume_full_last: bool,
+    ) -> Result<u32, FsError> {
+        self.ensure_writable()?;
+
+        let file = self.resolve(path, None)?.file;
+        if file.is_directory {
+            return Err(FsError::NotAFile);
+        }
+
+        let chain = self.checked_cluster_chain(file.start_cluster)?;
+        let cluster_size = self.sectors_per_cluster * self.bytes_per_sector;
+        let capacity = chain.len() as u32 * cluster_size;
+
+        let size = match chain.last() {
+            _ if self.verify_file(path).is_ok() => return Ok(file.size),
+            None => 0,
+            Some(_) if file.size > capacity || assume_full_last => capacity,
+            Some(&last) => {
+                let used = self
+                    .read_cluster(last)
+                    .iter()
+                    .rposition(|&byte| byte != 0)
+                    .map_or(1, |position| position as u32 + 1);
+                capacity - cluster_size + used
+            }
+        };
+
+        let location = self.locate_entry(path, None)?;
+        let mut entry = self.read_entry_at(location.cluster, location.offset_in_cluster)?;
+        let offset = DirOffsets::FileSize as usize;
+        entry[offset..offset + 4].copy_from_slice(&size.to_le_bytes());
+        self.write_entry_at(location.cluster, location.offset_in_cluster, &entry)?;
+
+        Ok(size)
+    }
+
+    /// Parcourt récursivement l’arborescence à partir de la racine.
+    ///
+    /// `visit` est appelé pour chaque entrée (hors `.` et `..`) avec son chemin absolu.
+    /// Une entrée pour laquelle `skip` retourne `true` est ignorée ; s’il s’agit d’un
+    /// répertoire, tout son sous-arbre est élagué.
+    /// Un répertoire déjà visité n’est pas parcouru une seconde fois.
+    pub fn walk<S: Fn(&FileInfo) -> bool, F: FnMut(&str, &FileInfo)>(&self, skip: S, mut visit: F) {
+        let mut visited = BTreeSet::new();
+        visited.insert(self.root_cluster);
+        self.walk_directory(self.root_cluster, "", &skip, &mut visit, &mut visited);
+    }
+
+    /// Parcourt un répertoire pour [`Self::walk`].
+    fn walk_directory<S: Fn(&FileInfo) -> bool, F: FnMut(&str, &FileInfo)>(
+        &self,
+        cluster: u32,
+        prefix: &str,
+        skip: &S,
+        visit: &mut F,
+        visited: &mut BTreeSet<u32>,
+    ) {
+        for file in list_directory_entries(self, cluster) {
+            if file.is_dot_entry || skip(&file) {
+                continue;
+            }
+
+            let path = format!("{}/{}", prefix, file.name);
+            visit(&path, &file);
+
+            if file.is_directory && file.start_cluster >= 2 && visited.insert(file.start_cluster) {
+                self.walk_directory(file.start_cluster, &path, skip, visit, visited);
+            }
+        }
+    }
+
+    /// Retourne le chemin de l’entrée dont la chaîne contient `cluster` (« à qui appartient
+    /// le cluster N ? »).
+    ///
+    /// La racine (`/`) est examinée en premier, puis l’arborescence dans l’ordre de
+    /// [`Self::walk`] ; seul le premier propriétaire est retourné.
+    pub fn find_by_cluster(&self, cluster: u32) -> Option<String> {
+        if self.cluster_chain(self.root_cluster).contains(&cluster) {
+            return Some("/".to_string());
+        }
+
+        let mut owner = None;
+        self.walk(
+            |_| false,
+            |path, file| {
+                if owner.is_none() && self.cluster_chain(file.start_cluster).contains(&cluster) {
+                    owner = Some(path.to_string());
+                }
+            },
+        );
+
+        owner
+    }
+
+    /// Recherche les clusters référencés par plusieurs chaînes (clusters croisés).
+    ///
+    /// Retourne chaque cluster concerné avec la liste des chemins qui l’utilisent.
+    /// Un cluster croisé indique une corruption : [`Self::read_file`] lirait les données d’un autre fichier.
+    pub fn find_cross_links(&self) -> Vec<(u32, Vec<String>)> {
+        let mut owners: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+
+        self.walk(
+            |_| false,
+            |path, file| {
+                for cluster in self.cluster_chain(file.start_cluster) {
+                    owners.entry(cluster).or_default().push(path.to_string());
+                }
+            },
+        );
+
+        owners
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .collect()
+    }
+
+    /// Parcourt la FAT table pour trouver un cluster libre, le réserve et retourne son index.
+    ///
+    /// La recherche commence à `hint` (voir [`Self::allocation_hint`] et
+    /// [`Self::extension_hint`]) puis reprend au cluster 2.
+    /// Un cluster est considéré libre si son entrée dans la FAT est `0x00000000`.
+    /// Une fois trouvé, il est marqué avec `0x0FFFFFFF` EOC (End of Chain).
+    ///
+    /// # Errors
+    /// Retourne [`FsError::DiskFull`] si aucun cluster libre n'est trouvé.
+    fn allocate_cluster(&mut self, hint: u32) -> Result<u32, FsError> {
+        let end = self.cluster_count() + 2;
+        let start = if (2..end).contains(&hint) { hint } else { 2 };
+
+        for cluster_id in (start..end).chain(2..start) {
+            let entry = self.read_fat_entry(cluster_id);
+            if entry == FAT_FREE {
+                self.write_fat_entry(cluster_id, FAT_EOC_MARK);
+                self.alloc_cursor = Some(cluster_id + 1);
+                self.write_fsinfo_field(492, cluster_id + 1);
+                return Ok(cluster_id);
+            }
+        }
+        Err(FsError::DiskFull)
+    }
+
+    /// Point de départ de l’allocation du premier cluster d’un fichier ou répertoire :
+    /// le cluster 2 en first-fit, le curseur (ou à défaut le FSInfo) en next-fit.
+    fn allocation_hint(&self) -> u32 {
+        match self.options.alloc_strategy {
+            AllocStrategy::FirstFit => 2,
+            AllocStrategy::NextFit => self
+                .alloc_cursor
+                .or_else(|| self.fsinfo_next_free())
+                .unwrap_or(2),
+        }
+    }
+
+    /// Point de départ de l’allocation d’un cluster ajouté après `last` dans une chaîne :
+    /// le cluster 2 en first-fit, le cluster suivant `last` en next-fit pour que la
+    /// chaîne reste contiguë.
+    fn extension_hint(&self, last: u32) -> u32 {
+        match self.options.alloc_strategy {
+            AllocStrategy::FirstFit => 2,
+            AllocStrategy::NextFit => last + 1,
+        }
+    }
+
+    /// Ajoute un cluster vide à la fin de la chaîne d’un répertoire et retourne son index.
+    ///
+    /// Le nouveau cluster est réservé (EOC) et remis à zéro avant d’être chaîné au dernier
+    /// cluster du répertoire : en cas d’échec de l’allocation, la chaîne reste inchangée.
+    ///
+    /// # Errors
+    /// Retourne [`FsError::DiskFull`] si aucun cluster n’est disponible.
+    fn extend_directory(&mut self, dir_cluster: u32) -> Result<u32, FsError> {
+        let last_cluster = *self
+            .cluster_chain(dir_cluster)
+            .last()
+            .ok_or(FsError::NotADirectory)?;
+
+        let new_cluster = self.allocate_cluster(self.extension_hint(last_cluster))?;
+
+        let cluster_size = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
+        let start_sector = self.cluster_to_sector(new_cluster)?;
+        let offset = (start_sector * self.bytes_per_sector) as usize;
+        self.disk[offset..offset + cluster_size].fill(0);
+
+        self.write_fat_entry(last_cluster, new_cluster);
+
+        Ok(new_cluster)
+    }
+
+    /// Écrit une valeur de 32 bits dans la FAT table.
+    ///
+    /// Cette fonction préserve les 4 bits de poids fort et ne modifie que les 28 bits d'adresse.
+    /// Toutes les copies de la FAT sont mises à jour, ainsi que le compteur de clusters
+    /// libres (voir [`Self::free_count_cached`]) et sa copie dans le FSInfo.
+    fn write_fat_entry(&mut self, cluster_id: u32, value: u32) {
+        let fat_offset = cluster_id * 4;
+        let counted = (2..self.cluster_count() + 2).contains(&cluster_id);
+        let mut changed = false;
+
+        // Le compteur doit être connu avant l’écriture pour être recopié dans le FSInfo
+        if counted && self.fsinfo_sector != 0 {
+            self.free_count_cached();
+        }
+
+        for copy in 0..self.num_fats.max(1) {
+            let sector_num = self.fat_sector
+                + copy * self.sectors_per_fat
+                + (fat_offset / self.bytes_per_sector);
+            let offset_in_sector = (fat_offset % self.bytes_per_sector) as usize;
+
+            let global_offset = (sector_num * self.bytes_per_sector) as usize + offset_in_sector;
+
+            let current_value = u32::from_le_bytes(
+                self.disk[global_offset..global_offset + 4]
+                    .try_into()
+                    .unwrap(),
+            );
+            let new_value = (current_value & !FAT_ENTRY_MASK) | (value & FAT_ENTRY_MASK);
+
+            if copy == 0 && counted {
+                let was_free = current_value & FAT_ENTRY_MASK == FAT_FREE;
+                let is_free = value & FAT_ENTRY_MASK == FAT_FREE;
+                changed = was_free != is_free;
+                let count = self.free_count.get();
+                self.free_count.set(
+                    count.map(|count| (count + is_free as u32).saturating_sub(was_free as u32)),
+                );
+            }
+
+            self.disk[global_offset..global_offset + 4].copy_from_slice(&new_value.to_le_bytes());
+        }
+
+        if let Some(count) = self.free_count.get().filter(|_| changed) {
+            self.write_fsinfo_field(488, count);
+        }
+    }
+
+    /// Convertit un nom de fichier standard en format court 8.3 (SFN).
+    ///
+    /// Le résultat est un tableau de 11 octets : 8 pour le nom et 3 pour l'extension, complété par des espaces et converti en majuscules.
+    ///
+    /// # Errors
+    /// Retourne une erreur si le nom est vide ou mal formaté.
+    fn format_to_8_3(name: &str) -> Result<[u8; 11], &str> {
+        if let Some(short_name) = to_short_name(name) {
+            return Ok(short_name);
+        }
+
+        let mut res = [b' '; 11];
+        let parts: Vec<&str> = name.split('.').collect();
+
+        if parts.is_empty() || parts[0].is_empty() {
+            return Err("Invalid name");
+        }
+
+        let name_part = parts[0].to_uppercase();
+        let name_bytes = name_part.as_bytes();
+        let name_len = name_bytes.len().min(8);
+        res[0..name_len].copy_from_slice(&name_bytes[..name_len]);
+
+        if parts.len() > 1 {
+            let ext_part = parts[1].to_uppercase();
+            let ext_bytes = ext_part.as_bytes();
+            let ext_len = ext_bytes.len().min(3);
+            res[8..8 + ext_len].copy_from_slice(&ext_bytes[..ext_len]);
+        }
+
+        Ok(res)
+    }
+
+    /// Crée un nouveau fichier vide dans le répertoire spécifié.
+    ///
+    /// Cette méthode crée une entrée de répertoire avec l'attribut `0x20` (Archive) et l'inscrit dans le cluster du répertoire parent.
+    /// Comme le prévoit la spécification, aucun cluster n’est alloué : le fichier vide a un cluster de départ nul.
+    ///
+    /// # Errors
+    /// Retourne une erreur si le parent est introuvable ou si le répertoire parent est plein.
+    pub fn create_file(&mut self, parent_path: &str, filename: &str) -> Result<(), String> {
+        self.ensure_writable()?;
+
+        let parent_cluster = if parent_path.is_empty() || parent_path == "/" {
+            self.root_cluster
+        } else {
+            self.parse_path(parent_path, None)
+                .map(|f| f.start_cluster)
+                .ok_or_else(|| "Parent directory not found".to_string())?
+        };
+
+        let short_name = Self::format_to_8_3(filename)?;
+
+        self.create_file_in(parent_cluster, short_name, &[])?;
+        Ok(())
+    }
+
+    /// Crée un fichier vide nommé `short_name` dans le répertoire `parent_cluster`.
+    ///
+    /// Les fragments `lfn` (vides pour un nom purement 8.3, voir [`name_entries`]) sont
+    /// écrits juste avant l’entrée 8.3. Le cluster de départ reste à 0 : le premier
+    /// cluster n’est alloué qu’à la première écriture (voir [`Self::append_at`]).
+    /// Retourne l’emplacement de la nouvelle entrée.
+    fn create_file_in(
+        &mut self,
+        parent_cluster: u32,
+        short_name: [u8; 11],
+        lfn: &[[u8; 32]],
+    ) -> Result<EntryLocation, FsError> {
+        let mut new_entry = [0u8; 32];
+        new_entry[0..11].copy_from_slice(&short_name);
+        new_entry[11] = ATTR_ARCHIVE;
+        self.stamp_new_entry(&mut new_entry);
+
+        self.write_entry_run(parent_cluster, lfn, &new_entry)
+    }
+
+    /// Écrit les fragments `lfn` suivis de l’entrée 8.3 `entry` dans des slots libres
+    /// consécutifs de `dir_cluster` (agrandi si besoin) et retourne l’emplacement de `entry`.
+    fn write_entry_run(
+        &mut self,
+        dir_cluster: u32,
+        lfn: &[[u8; 32]],
+        entry: &[u8; 32],
+    ) -> Result<EntryLocation, FsError> {
+        let run = self.find_free_run(dir_cluster, lfn.len() + 1)?;
+
+        for (&(cluster, idx), bytes) in run.iter().zip(lfn.iter().chain([entry])) {
+            self.write_entry_at(cluster, idx * 32, bytes)?;
+        }
+
+        let &(cluster, idx) = run.last().ok_or(FsError::NotFound)?;
+        Ok(EntryLocation {
+            cluster,
+            offset_in_cluster: idx * 32,
+        })
+    }
+
+    /// Écrit une entrée de répertoire de 32 octets à `offset_in_cluster` dans un cluster.
+    ///
+    /// Primitive d’écriture commune à toutes les opérations modifiant un répertoire
+    /// (`touch`, `mkdir`, `rm`, `undelete`…). L’image étant en mémoire, l’écriture est
+    /// immédiatement visible : aucun secteur n’est mis en cache.
+    ///
+    /// # Errors
+    /// - [`FsError::InvalidCluster`] si le cluster n’est pas un cluster de données
+    /// - [`FsError::OutOfBounds`] si l’offset n’est pas aligné sur 32 octets,
+    ///   sort du cluster ou si le cluster est hors du disque
+    fn write_entry_at(
+        &mut self,
+        cluster: u32,
+        offset_in_cluster: usize,
+        entry: &[u8; 32],
+    ) -> Result<(), FsError> {
+        let offset = self.entry_offset(cluster, offset_in_cluster)?;
+        self.disk[offset..offset + 32].copy_from_slice(entry);
+        Ok(())
+    }
+
+    /// Lit l’entrée de répertoire de 32 octets située à `offset_in_cluster` dans un cluster.
+    ///
+    /// # Errors
+    /// Mêmes conditions que [`Self::write_entry_at`].
+    fn read_entry_at(&self, cluster: u32, offset_in_cluster: usize) -> Result<[u8; 32], FsError> {
+        let offset = self.entry_offset(cluster, offset_in_cluster)?;
+        Ok(self.disk[offset..offset + 32].try_into().unwrap())
+    }
+
+    /// Retourne l’emplacement de l’entrée 8.3 désignée par un chemin.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`]
+    /// - [`FsError::NotFound`] si le chemin désigne la racine ou une entrée synthétique (`..`)
+    pub fn locate_entry(
+        &self,
+        path: &str,
+        current_cluster: Option<u32>,
+    ) -> Result<EntryLocation, FsError> {
+        let resolved = self.resolve(path, current_cluster)?;
+
+        read_directory_slots(self, resolved.parent_cluster, false)
+            .into_iter()
+            .find(|(f, _)| *f == resolved.file)
+            .and_then(|(_, slots)| slots.last().copied())
+            .map(|(cluster, idx)| EntryLocation {
+                cluster,
+                offset_in_cluster: idx * 32,
+            })
+            .ok_or(FsError::NotFound)
+    }
+
+    /// Reconstruit le chemin absolu de l’entrée située à `loc`.
+    ///
+    /// Pendant de [`Self::locate_entry`] : le répertoire contenant l’entrée est remonté
+    /// jusqu’à la racine via ses entrées `..`, et le nom de chaque répertoire est
+    /// retrouvé en cherchant son cluster dans le répertoire parent.
+    ///
+    /// Retourne `None` si `loc` ne désigne pas une entrée valide (slot libre, entrée
+    /// `.`/`..`, fragment LFN) ou si la remontée échoue (`..` manquant, cycle).
+    pub fn path_of(&self, loc: &EntryLocation) -> Option<String> {
+        let dir = self.chain_head(loc.cluster);
+        let slot = (loc.cluster, loc.offset_in_cluster / 32);
+        let (entry, _) = read_directory_slots(self, dir, false)
+            .into_iter()
+            .find(|(_, slots)| slots.last() == Some(&slot))?;
+        if entry.is_dot_entry {
+            return None;
+        }
+
+        let mut names = vec![entry.name];
+        let mut visited = BTreeSet::new();
+        let mut cluster = dir;
+
+        while cluster != self.root_cluster {
+            if !visited.insert(cluster) {
+                return None;
+            }
+            let parent = self.find_parent_cluster(cluster)?;
+            let dir_entry = self
+                .dir_iter(parent)
+                .find(|f| f.is_directory && !f.is_dot_entry && f.start_cluster == cluster)?;
+            names.push(dir_entry.name);
+            cluster = parent;
+        }
+
+        names.reverse();
+        Some(format!("/{}", names.join("/")))
+    }
+
+    /// Retourne le premier cluster de la chaîne contenant `cluster`.
+    ///
+    /// La FAT ne chaînant que vers l’avant, la table des prédécesseurs est construite en
+    /// un seul passage sur la FAT ; la remontée s’arrête sur un cycle.
+    fn chain_head(&self, cluster: u32) -> u32 {
+        let mut previous = BTreeMap::new();
+        for (prev, &next) in self.fat_entries().iter().enumerate().skip(2) {
+            if self.is_valid_cluster(next) {
+                previous.entry(next).or_insert(prev as u32);
+            }
+        }
+
+        let mut head = cluster;
+        let mut visited = BTreeSet::new();
+        while visited.insert(head) {
+            match previous.get(&head) {
+                Some(&prev) => head = prev,
+                None => break,
+            }
+        }
+        head
+    }
+
+    /// Restaure un fichier supprimé (`undelete`).
+    ///
+    /// `deleted_name` est le nom retourné par [`list_deleted`] (premier caractère `?`).
+    /// Le premier octet de l’entrée est remplacé par le premier caractère de `restore_name`.
+    /// La chaîne étant effacée de la FAT lors de la suppression, elle est supposée contiguë
+    /// à partir du cluster de départ, puis de nouveau marquée comme utilisée.
+    ///
+    /// # Errors
+    /// - [`FsError::NotFound`] si le répertoire ou l’entrée supprimée n’existe pas
+    /// - [`FsError::InvalidName`] si `restore_name` n’est pas un nom 8.3 valide
+    /// - [`FsError::InvalidCluster`] si la chaîne supposée sort de la zone de données
+    /// - [`FsError::ClusterReallocated`] si un cluster de la chaîne a été réalloué
+    ///
+    /// Rien n’est écrit tant que toutes ces vérifications n’ont pas réussi.
+    pub fn undelete(
+        &mut self,
+        dir_path: &str,
+        deleted_name: &str,
+        restore_name: &str,
+    ) -> Result<(), FsError> {
+        self.ensure_writable()?;
+
+        let dir_cluster = if dir_path.is_empty() || dir_path == "/" {
+            self.root_cluster
+        } else {
+            let resolved = self.resolve(dir_path, None)?;
+            if !resolved.file.is_directory {
+                return Err(FsError::NotADirectory);
+            }
+            resolved.file.start_cluster
+        };
+
+        let first_char = to_short_name(restore_name).ok_or(FsError::InvalidName)?[0];
+
+        let (file, (entry_cluster, idx)) = read_deleted_entries(self, dir_cluster)
+            .into_iter()
+            .find(|(f, _)| f.name == deleted_name)
+            .ok_or(FsError::NotFound)?;
+
+        let cluster_bytes = self.bytes_per_sector * self.sectors_per_cluster;
+        let cluster_count = file.size.div_ceil(cluster_bytes).max(1);
+        let chain = if file.start_cluster == 0 {
+            0..0
+        } else {
+            let end = file
+                .start_cluster
+                .checked_add(cluster_count)
+                .ok_or(FsError::InvalidCluster)?;
+            file.start_cluster..end
+        };
+
+        if !chain.clone().all(|cluster| self.is_valid_cluster(cluster)) {
+            return Err(FsError::InvalidCluster);
+        }
+
+        if chain
+            .clone()
+            .any(|cluster| self.read_fat_entry(cluster) != FAT_FREE)
+        {
+            return Err(FsError::ClusterReallocated);
+        }
+
+        // Une entrée vivante pointant déjà sur l’un de ces clusters interdit la restauration
+        let mut cross_linked = false;
+        self.walk(
+            |_| false,
+            |_, live| {
+                cross_linked |= self
+                    .cluster_chain(live.start_cluster)
+                    .iter()
+                    .any(|cluster| chain.contains(cluster));
+            },
+        );
+        if cross_linked {
+            return Err(FsError::ClusterReallocated);
+        }
+
+        for cluster in chain.clone() {
+            let next = if cluster + 1 < chain.end {
+                cluster + 1
+            } else {
+                FAT_EOC_MARK
+            };
+            self.write_fat_entry(cluster, next);
+        }
+
+        let mut entry = self.read_entry_at(entry_cluster, idx * 32)?;
+        entry[0] = first_char;
+        self.write_entry_at(entry_cluster, idx * 32, &entry)?;
+
+        Ok(())
+    }
+
+    /// Calcule l’offset sur le disque d’une entrée de 32 octets d’un cluster de répertoire.
+    fn entry_offset(&self, cluster: u32, offset_in_cluster: usize) -> Result<usize, FsError> {
+        let cluster_bytes = (self.bytes_per_sector * self.sectors_per_cluster) as usize;
+        if !offset_in_cluster.is_multiple_of(32) || offset_in_cluster + 32 > cluster_bytes {
+            return Err(FsError::OutOfBounds);
+        }
+
+        let start_sector = self.cluster_to_sector(cluster)?;
+        let sector_offset = (offset_in_cluster as u32) / self.bytes_per_sector;
+        let byte_offset_in_sector = offset_in_cluster % (self.bytes_per_sector as usize);
+
+        let offset = ((start_sector + sector_offset) * self.bytes_per_sector) as usize
+            + byte_offset_in_sector;
 
-        if offset + size > self.disk.len() {
-            panic!("Error reading outbound");
+        if offset + 32 > self.disk.len() {
+            return Err(FsError::OutOfBounds);
         }
 
-        self.disk[offset..offset + size].to_vec()
+        Ok(offset)
     }
 
-    /// Lit un cluster complet (tous ses secteurs).
-    pub fn read_cluster(&self, cluster_id: u32) -> Vec<u8> {
-        let start_address = self.data_sector + (cluster_id - 2) * self.sectors_per_cluster;
-        let mut data = Vec::new();
-
-        for i in 0..self.sectors_per_cluster {
-            let sector_data = self.read_sector(start_address + i);
-            data.extend(sector_data);
+    /// Libère tous les clusters de la chaîne débutant à `start_cluster`.
+    fn free_chain(&mut self, start_cluster: u32) {
+        for cluster in self.cluster_chain(start_cluster) {
+            self.release_cluster(cluster);
         }
-
-        data
     }
 
-    /// Lit une entrée FAT pour obtenir le cluster suivant.
-    ///
-    /// Les bits de poids fort sont masqués conformément à la spécification FAT32.
-    fn read_fat_entry(&self, cluster_id: u32) -> u32 {
-        let fat_offset = cluster_id * 4;
-        let fat_sector = self.fat_sector + fat_offset / self.bytes_per_sector;
-        let fat_index = (fat_offset % self.bytes_per_sector) as usize;
-        let sector = self.read_sector(fat_sector);
+    /// Marque un cluster libre dans la FAT, après l’avoir rempli de zéros si
+    /// [`MountOptions::wipe_on_free`] est activé.
+    fn release_cluster(&mut self, cluster: u32) {
+        if self.options.wipe_on_free {
+            let cluster_size = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
+            if let Ok(sector) = self.cluster_to_sector(cluster) {
+                let offset = (sector * self.bytes_per_sector) as usize;
+                if let Some(data) = self.disk.get_mut(offset..offset + cluster_size) {
+                    data.fill(0);
+                }
+            }
+        }
 
-        let entry = u32::from_le_bytes(sector[fat_index..fat_index + 4].try_into().unwrap());
-        entry & 0x0FFFFFFF
+        self.write_fat_entry(cluster, FAT_FREE);
     }
 
-    /// Lit le contenu d’un fichier texte à partir de son chemin.
+    /// Supprime un fichier (`rm`).
     ///
-    /// - Supporte les chemins absolus et relatifs
-    /// - Gère les chaînes de clusters FAT
+    /// Les slots de l’entrée (fragments LFN et entrée 8.3) sont marqués `0xE5`
+    /// et sa chaîne de clusters est libérée dans la FAT. Les données restent sur le disque.
     ///
     /// # Errors
-    /// - `"File not found"`
-    /// - `"Not a file"`
-    /// - `"Invalid UTF-8 content"`
-    pub fn read_file(&self, path: &str, current_cluster: Option<u32>) -> Result<String, &str> {
-        let file = self
-            .parse_path(path, current_cluster)
-            .ok_or("File not found")?;
+    /// - [`FsError::NotFound`] si le fichier n’existe pas
+    /// - [`FsError::NotAFile`] si le chemin désigne un répertoire
+    pub fn remove_file(&mut self, path: &str, current_cluster: Option<u32>) -> Result<(), FsError> {
+        self.ensure_writable()?;
 
-        if file.is_directory {
-            return Err("Not a file");
+        let resolved = self.resolve(path, current_cluster)?;
+
+        if resolved.file.is_directory {
+            return Err(FsError::NotAFile);
         }
 
-        let mut data = Vec::new();
-        let mut cluster = file.start_cluster;
+        let (file, slots) = read_directory_slots(self, resolved.parent_cluster, false)
+            .into_iter()
+            .find(|(f, _)| *f == resolved.file)
+            .ok_or(FsError::NotFound)?;
 
-        loop {
-            data.extend(self.read_cluster(cluster));
-            let next = self.read_fat_entry(cluster);
+        self.mark_slots_deleted(&slots)?;
+        self.free_chain(file.start_cluster);
 
-            if next >= 0x0FFFFFF8 {
-                break;
-            }
+        Ok(())
+    }
 
-            cluster = next;
+    /// Marque `0xE5` les slots `(cluster, index)` d’une entrée et retourne leur contenu
+    /// d’origine, pour permettre de les restaurer.
+    fn mark_slots_deleted(&mut self, slots: &[(u32, usize)]) -> Result<Vec<[u8; 32]>, FsError> {
+        let mut saved = Vec::with_capacity(slots.len());
+        for &(cluster, idx) in slots {
+            let old = self.read_entry_at(cluster, idx * 32)?;
+            let mut deleted = old;
+            deleted[0] = 0xE5;
+            self.write_entry_at(cluster, idx * 32, &deleted)?;
+            saved.push(old);
         }
-
-        data.truncate(file.size as usize);
-        String::from_utf8(data).map_err(|_| "Invalid UTF-8 content")
+        Ok(saved)
     }
 
-    /// Résout un chemin en parcourant récursivement les répertoires.
-    fn parse_path(&self, path: &str, current_cluster: Option<u32>) -> Option<FileInfo> {
-        let mut cluster = if path.starts_with("/") || path.is_empty() {
-            self.root_cluster
-        } else {
-            current_cluster.unwrap_or(self.root_cluster)
-        };
+    /// Efface l’entrée située à `location` dans le répertoire `parent_cluster` (fragments
+    /// LFN compris) et libère sa chaîne, comme [`Self::remove_file`].
+    ///
+    /// Sert à retirer une destination laissée incomplète par une écriture en échec.
+    fn discard_entry(
+        &mut self,
+        parent_cluster: u32,
+        location: &EntryLocation,
+    ) -> Result<(), FsError> {
+        let slot = (location.cluster, location.offset_in_cluster / 32);
+        let (file, slots) = read_directory_slots(self, parent_cluster, false)
+            .into_iter()
+            .find(|(_, slots)| slots.last() == Some(&slot))
+            .ok_or(FsError::NotFound)?;
+
+        self.mark_slots_deleted(&slots)?;
+        self.free_chain(file.start_cluster);
 
-        let parts: Vec<&str> = path.split("/").filter(|s| !s.is_empty()).collect();
+        Ok(())
+    }
 
-        for (i, part) in parts.iter().enumerate() {
-            let files = list_directory_entries(self, cluster);
+    /// Compacte un répertoire et libère les clusters de fin devenus inutiles.
+    ///
+    /// Les slots vivants (fragments LFN compris) sont regroupés au début du répertoire
+    /// dans leur ordre d’origine, ce qui conserve `.` et `..` en tête ; les slots supprimés
+    /// (`0xE5`) disparaissent. La chaîne est tronquée au nombre de clusters nécessaire
+    /// (au moins un) et le surplus est libéré dans la FAT.
+    ///
+    /// Retourne le nombre de clusters libérés.
+    ///
+    /// # Errors
+    /// Retourne [`FsError::InvalidCluster`] si `dir_cluster` n’est pas un cluster de données.
+    pub fn compact_directory(&mut self, dir_cluster: u32) -> Result<u32, FsError> {
+        self.ensure_writable()?;
 
-            match *part {
-                "." => continue,
-                ".." => {
-                    cluster = self.find_parent_cluster(cluster)?;
-                    if i == parts.len() - 1 {
-                        return Some(FileInfo::new("..".to_string(), true, 0, cluster));
-                    }
-                    continue;
-                }
-                _ => {}
-            }
+        if !self.is_valid_cluster(dir_cluster) {
+            return Err(FsError::InvalidCluster);
+        }
 
-            let file = files.iter().find(|f| f.name == *part)?.clone();
+        let chain = self.cluster_chain(dir_cluster);
 
-            if i == parts.len() - 1 {
-                return Some(file);
-            }
+        let cluster_size = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
 
-            if !file.is_directory {
-                return None;
+        let mut live = Vec::new();
+        'chain: for &cluster in &chain {
+            for entry in self.read_cluster(cluster).chunks_exact(32) {
+                match entry[0] {
+                    0x00 => break 'chain,
+                    0xE5 => {}
+                    _ => live.extend_from_slice(entry),
+                }
             }
-
-            cluster = file.start_cluster;
         }
 
-        None
-    }
+        let needed = live.len().div_ceil(cluster_size).max(1);
+        live.resize(needed * cluster_size, 0);
 
-    /// Recherche le cluster parent d’un répertoire via l’entrée `..`.
-    fn find_parent_cluster(&self, current_cluster: u32) -> Option<u32> {
-        if current_cluster == self.root_cluster {
-            return None;
+        for (&cluster, data) in chain.iter().zip(live.chunks_exact(cluster_size)) {
+            let offset = (self.cluster_to_sector(cluster)? * self.bytes_per_sector) as usize;
+            self.disk[offset..offset + cluster_size].copy_from_slice(data);
         }
 
-        let files = list_directory_entries(self, current_cluster);
-        let parent = files.iter().find(|f| f.name == "..")?;
+        if needed < chain.len() {
+            self.write_fat_entry(chain[needed - 1], FAT_EOC_MARK);
+            for &cluster in &chain[needed..] {
+                self.release_cluster(cluster);
+            }
+        }
 
-        Some(if parent.start_cluster == 0 {
-            self.root_cluster
-        } else {
-            parent.start_cluster
-        })
+        Ok((chain.len() - needed) as u32)
     }
 
-    /// Parcourt la FAT table pour trouver le premier cluster libre, le réserve et retourne son index.
+    /// Déplace le contenu du cluster `old` vers le cluster libre `new` (brique de base de
+    /// la défragmentation).
     ///
-    /// Un cluster est considéré libre si son entrée dans la FAT est `0x00000000`.
-    /// Une fois trouvé, il est marqué avec `0x0FFFFFFF` EOC (End of Chain).
+    /// Les données sont copiées, `new` reprend l’entrée FAT de `old` (suite de la chaîne
+    /// ou EOC), puis le lien vers `old` est reporté : l’entrée FAT de `predecessor`, ou à
+    /// défaut le cluster de départ de l’entrée de répertoire dont la chaîne débute à `old`.
+    /// Pour le premier cluster d’un répertoire, son entrée `.` et le `..` de ses
+    /// sous-répertoires sont également mis à jour. `old` est enfin libéré.
     ///
     /// # Errors
-    /// Retourne une erreur si aucun cluster libre n'est trouvé dans la limite des 50 000 premiers clusters.
-    fn allocate_cluster(&mut self) -> Result<u32, String> {
-        for cluster_id in 2..50000 {
-            let entry = self.read_fat_entry(cluster_id);
-            if entry == 0x00000000 {
-                self.write_fat_entry(cluster_id, 0x0FFFFFFF);
-                return Ok(cluster_id);
+    /// - [`FsError::InvalidCluster`] si `old` ou `new` n’est pas un cluster de données,
+    ///   si `new` n’est pas libre, si `predecessor` ne pointe pas sur `old` ou si `old`
+    ///   est le premier cluster de la racine (référencé par le secteur de boot)
+    /// - [`FsError::NotFound`] si, sans `predecessor`, aucune entrée ne débute à `old`
+    pub fn relocate_cluster(
+        &mut self,
+        old: u32,
+        new: u32,
+        predecessor: Option<u32>,
+    ) -> Result<(), FsError> {
+        self.ensure_writable()?;
+
+        if !self.is_valid_cluster(old)
+            || !self.is_valid_cluster(new)
+            || old == new
+            || self.read_fat_entry(new) != FAT_FREE
+        {
+            return Err(FsError::InvalidCluster);
+        }
+
+        // Entrée de répertoire à mettre à jour lorsque `old` débute une chaîne
+        let owner = match predecessor {
+            Some(previous) if self.read_fat_entry(previous) == old => None,
+            Some(_) => return Err(FsError::InvalidCluster),
+            None if old == self.root_cluster => return Err(FsError::InvalidCluster),
+            None => {
+                let mut owner = None;
+                self.walk(
+                    |_| false,
+                    |path, file| {
+                        if owner.is_none() && file.start_cluster == old {
+                            owner = Some((path.to_string(), file.is_directory));
+                        }
+                    },
+                );
+                let (path, is_directory) = owner.ok_or(FsError::NotFound)?;
+                Some((self.locate_entry(&path, None)?, is_directory))
+            }
+        };
+
+        let cluster_size = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
+        let data = self.read_cluster(old);
+        let offset = (self.cluster_to_sector(new)? * self.bytes_per_sector) as usize;
+        self.disk[offset..offset + cluster_size].copy_from_slice(&data[..cluster_size]);
+        self.write_fat_entry(new, self.read_fat_entry(old));
+
+        match (predecessor, owner) {
+            (Some(previous), _) => self.write_fat_entry(previous, new),
+            (None, Some((location, is_directory))) => {
+                let mut entry =
+                    FatDir::new(&self.read_entry_at(location.cluster, location.offset_in_cluster)?);
+                entry.set_first_cluster(new);
+                self.write_entry_at(
+                    location.cluster,
+                    location.offset_in_cluster,
+                    &entry.to_bytes(),
+                )?;
+
+                if is_directory {
+                    self.repoint_dot_entries(new)?;
+                }
             }
+            (None, None) => {}
         }
-        Err("Disk full".to_string())
+
+        self.release_cluster(old);
+        Ok(())
     }
 
-    /// Écrit une valeur de 32 bits dans la FAT table.
-    ///
-    /// Cette fonction préserve les 4 bits de poids fort et ne modifie que les 28 bits d'adresse.
-    fn write_fat_entry(&mut self, cluster_id: u32, value: u32) {
-        let fat_offset = cluster_id * 4;
-        let sector_num = self.fat_sector + (fat_offset / self.bytes_per_sector);
-        let offset_in_sector = (fat_offset % self.bytes_per_sector) as usize;
+    /// Fait pointer sur `dir_cluster` l’entrée `.` du répertoire et le `..` de chacun de
+    /// ses sous-répertoires, après un déplacement de son premier cluster.
+    fn repoint_dot_entries(&mut self, dir_cluster: u32) -> Result<(), FsError> {
+        let mut dot = FatDir::new(&self.read_entry_at(dir_cluster, 0)?);
+        if &dot.name == b".          " {
+            dot.set_first_cluster(dir_cluster);
+            self.write_entry_at(dir_cluster, 0, &dot.to_bytes())?;
+        }
 
-        let global_offset = (sector_num * self.bytes_per_sector) as usize + offset_in_sector;
+        let children: Vec<u32> = list_directory_entries(self, dir_cluster)
+            .into_iter()
+            .filter(|f| f.is_directory && !f.is_dot_entry && f.start_cluster >= 2)
+            .map(|f| f.start_cluster)
+            .collect();
 
-        let current_value = u32::from_le_bytes(
-            self.disk[global_offset..global_offset + 4]
-                .try_into()
-                .unwrap(),
-        );
-        let new_value = (current_value & 0xF0000000) | (value & 0x0FFFFFFF);
+        for child in children {
+            self.fix_dotdot(child, dir_cluster)?;
+        }
 
-        self.disk[global_offset..global_offset + 4].copy_from_slice(&new_value.to_le_bytes());
+        Ok(())
     }
 
-    /// Convertit un nom de fichier standard en format court 8.3 (SFN).
+    /// Fait pointer l’entrée `..` du répertoire `dir_cluster` sur `new_parent` (0 si le
+    /// nouveau parent est la racine, conformément à la spécification).
     ///
-    /// Le résultat est un tableau de 11 octets : 8 pour le nom et 3 pour l'extension, complété par des espaces et converti en majuscules.
+    /// Indispensable après un déplacement : sans elle, `cd ..` mènerait à l’ancien parent.
+    /// Un répertoire dont le second slot n’est pas `..` est laissé intact.
     ///
     /// # Errors
-    /// Retourne une erreur si le nom est vide ou mal formaté.
-    fn format_to_8_3(name: &str) -> Result<[u8; 11], &str> {
-        let mut res = [b' '; 11];
-        let parts: Vec<&str> = name.split('.').collect();
-
-        if parts.is_empty() || parts[0].is_empty() {
-            return Err("Invalid name");
-        }
-
-        let name_part = parts[0].to_uppercase();
-        let name_bytes = name_part.as_bytes();
-        let name_len = name_bytes.len().min(8);
-        res[0..name_len].copy_from_slice(&name_bytes[..name_len]);
-
-        if parts.len() > 1 {
-            let ext_part = parts[1].to_uppercase();
-            let ext_bytes = ext_part.as_bytes();
-            let ext_len = ext_bytes.len().min(3);
-            res[8..8 + ext_len].copy_from_slice(&ext_bytes[..ext_len]);
+    /// Erreurs de [`Self::write_entry_at`]
+    fn fix_dotdot(&mut self, dir_cluster: u32, new_parent: u32) -> Result<(), FsError> {
+        let mut dot_dot = FatDir::new(&self.read_entry_at(dir_cluster, 32)?);
+        if &dot_dot.name != b"..         " {
+            return Ok(());
         }
 
-        Ok(res)
+        dot_dot.set_first_cluster(if new_parent == self.root_cluster {
+            0
+        } else {
+            new_parent
+        });
+        self.write_entry_at(dir_cluster, 32, &dot_dot.to_bytes())
     }
 
-    /// Crée un nouveau fichier vide dans le répertoire spécifié.
+    /// Renomme une entrée sans toucher à sa chaîne de clusters.
     ///
-    /// Cette méthode alloue un cluster, crée une entrée de répertoire avec l'attribut `0x20` (Archive) et l'inscrit dans le cluster du répertoire parent.
+    /// Le nombre de slots nécessaires peut changer (nom court ↔ nom long) : les anciens
+    /// slots (fragments LFN et entrée 8.3) sont marqués `0xE5`, puis une suite contiguë de
+    /// slots libres est recherchée dans le même répertoire, agrandi si besoin. L’entrée 8.3
+    /// conserve ses horodatages, ses attributs, sa taille et son cluster de départ.
     ///
     /// # Errors
-    /// Retourne une erreur si le parent est introuvable ou si le répertoire parent est plein.
-    pub fn create_file(&mut self, parent_path: &str, filename: &str) -> Result<(), String> {
-        let parent_cluster = if parent_path.is_empty() || parent_path == "/" {
-            self.root_cluster
-        } else {
-            self.parse_path(parent_path, None)
-                .map(|f| f.start_cluster)
-                .ok_or_else(|| "Parent directory not found".to_string())?
-        };
+    /// - Erreurs de [`Self::resolve`]
+    /// - [`FsError::InvalidName`] si `new_name` est vide, réservé, contient un caractère
+    ///   interdit ou dépasse 255 caractères
+    /// - [`FsError::AlreadyExists`] si une autre entrée du répertoire porte déjà ce nom
+    /// - [`FsError::DiskFull`] si le répertoire ne peut pas être agrandi (l’entrée est restaurée)
+    pub fn rename_preserving_clusters(
+        &mut self,
+        path: &str,
+        new_name: &str,
+    ) -> Result<(), FsError> {
+        self.ensure_writable()?;
+
+        let new_name = new_name.trim_end_matches([' ', '.']);
+        let resolved = self.resolve(path, None)?;
+        let parent = resolved.parent_cluster;
+
+        let entries = read_directory_slots(self, parent, false);
+        let old_slots = entries
+            .iter()
+            .find(|(f, _)| *f == resolved.file)
+            .map(|(_, slots)| slots.clone())
+            .ok_or(FsError::NotFound)?;
+
+        let (short_name, lfn) = self.entry_names(parent, new_name, Some(&resolved.file))?;
+
+        let &(sfn_cluster, sfn_idx) = old_slots.last().ok_or(FsError::NotFound)?;
+        let mut entry = file_info_to_entry(
+            &resolved.file,
+            &self.read_entry_at(sfn_cluster, sfn_idx * 32)?,
+        );
+        entry[0..11].copy_from_slice(&short_name);
 
-        let short_name = Self::format_to_8_3(filename)?;
+        let saved = self.mark_slots_deleted(&old_slots)?;
 
-        let new_file_cluster = self.allocate_cluster()?;
+        if let Err(error) = self.write_entry_run(parent, &lfn, &entry) {
+            for (&(cluster, idx), old) in old_slots.iter().zip(&saved) {
+                self.write_entry_at(cluster, idx * 32, old)?;
+            }
+            return Err(error);
+        }
 
-        let idx = {
-            let cluster_data = self.read_cluster(parent_cluster);
-            cluster_data
-                .chunks_exact(32)
-                .enumerate()
-                .find(|(_, chunk)| chunk[0] == 0x00 || chunk[0] == 0xE5)
-                .map(|(i, _)| i)
-                .ok_or_else(|| "No space in parent directory".to_string())?
-        };
+        Ok(())
+    }
 
-        let mut new_entry = [0u8; 32];
-        new_entry[0..11].copy_from_slice(&short_name);
-        new_entry[11] = 0x20;
+    /// Calcule le nom court et les fragments LFN d’une nouvelle entrée `name` de `dir_cluster`.
+    ///
+    /// `exclude` désigne une entrée du répertoire à ignorer (l’entrée renommée).
+    ///
+    /// # Errors
+    /// - [`FsError::InvalidName`] si `name` n’est pas un nom valide (voir [`name_entries`])
+    /// - [`FsError::AlreadyExists`] si une autre entrée du répertoire porte déjà ce nom
+    fn entry_names(
+        &self,
+        dir_cluster: u32,
+        name: &str,
+        exclude: Option<&FileInfo>,
+    ) -> Result<([u8; 11], Vec<[u8; 32]>), FsError> {
+        let mut taken = BTreeSet::new();
+        for (f, slots) in read_directory_slots(self, dir_cluster, false)
+            .iter()
+            .filter(|(f, _)| Some(f) != exclude)
+        {
+            if f.name.eq_ignore_ascii_case(name) {
+                return Err(FsError::AlreadyExists);
+            }
+            if let Some(&(cluster, idx)) = slots.last() {
+                taken.insert(FatDir::new(&self.read_entry_at(cluster, idx * 32)?).name);
+            }
+        }
 
-        let high = (new_file_cluster >> 16) as u16;
-        let low = (new_file_cluster & 0xFFFF) as u16;
-        new_entry[20..22].copy_from_slice(&high.to_le_bytes());
-        new_entry[26..28].copy_from_slice(&low.to_le_bytes());
+        let (short_name, lfn) = name_entries(name, &taken).ok_or(FsError::InvalidName)?;
+        if lfn.is_empty() && taken.contains(&short_name) {
+            return Err(FsError::AlreadyExists);
+        }
 
-        self.write_directory_entry(parent_cluster, idx, new_entry);
+        Ok((short_name, lfn))
+    }
 
-        Ok(())
+    /// Compte les slots libres (`0x00` ou `0xE5`) de toute la chaîne d’un répertoire.
+    ///
+    /// Indique combien d’entrées 8.3 (ou de fragments LFN) le répertoire peut encore
+    /// recevoir sans être agrandi par [`Self::extend_directory`] ; les slots libres n’étant
+    /// pas forcément consécutifs, un nom long peut toutefois nécessiter un agrandissement.
+    pub fn dir_free_slots(&self, dir_cluster: u32) -> usize {
+        self.cluster_chain(dir_cluster)
+            .into_iter()
+            .map(|cluster| {
+                self.read_cluster(cluster)
+                    .chunks_exact(32)
+                    .filter(|chunk| chunk[0] == 0x00 || chunk[0] == 0xE5)
+                    .count()
+            })
+            .sum()
     }
 
-    /// Écrit une entrée de répertoire de 32 octets sur le disque.
-    fn write_directory_entry(&mut self, cluster_id: u32, entry_idx: usize, data: [u8; 32]) {
-        let start_sector = self.data_sector + (cluster_id - 2) * self.sectors_per_cluster;
-        let offset_in_cluster = entry_idx * 32;
+    /// Recherche `count` slots libres consécutifs (`0x00` ou `0xE5`) dans un répertoire.
+    ///
+    /// Une suite peut chevaucher deux clusters de la chaîne. Si aucune ne convient, le
+    /// répertoire est agrandi via [`Self::extend_directory`] autant que nécessaire.
+    fn find_free_run(
+        &mut self,
+        dir_cluster: u32,
+        count: usize,
+    ) -> Result<Vec<(u32, usize)>, FsError> {
+        let mut run = Vec::with_capacity(count);
+
+        for cluster in self.cluster_chain(dir_cluster) {
+            for (idx, chunk) in self.read_cluster(cluster).chunks_exact(32).enumerate() {
+                if chunk[0] != 0x00 && chunk[0] != 0xE5 {
+                    run.clear();
+                    continue;
+                }
 
-        let sector_offset = (offset_in_cluster as u32) / self.bytes_per_sector;
-        let byte_offset_in_sector = offset_in_cluster % (self.bytes_per_sector as usize);
+                run.push((cluster, idx));
+                if run.len() == count {
+                    return Ok(run);
+                }
+            }
+        }
 
-        let global_offset = ((start_sector + sector_offset) * self.bytes_per_sector) as usize
-            + byte_offset_in_sector;
+        let entries_per_cluster = (self.sectors_per_cluster * self.bytes_per_sector) as usize / 32;
+        while run.len() < count {
+            let cluster = self.extend_directory(dir_cluster)?;
+            run.extend((0..entries_per_cluster).map(|idx| (cluster, idx)));
+        }
+        run.truncate(count);
 
-        self.disk[global_offset..global_offset + 32].copy_from_slice(&data);
+        Ok(run)
     }
 
     /// Initialise un nouveau cluster de répertoire avec les entrées obligatoires `.` et `..`.
@@ -351,17 +2391,26 @@ impl Fat32FileSystem {
     /// * `.` pointe vers le cluster lui-même (`current_cluster`).
     /// * `..` pointe vers le cluster parent (`parent_cluster`). Si le parent est la racine,
     ///   la valeur 0 est utilisée conformément à la spécification.
-    fn init_directory_cluster(&mut self, current_cluster: u32, parent_cluster: u32) {
+    ///
+    /// # Errors
+    /// Retourne [`FsError::InvalidCluster`] si `current_cluster` n’est pas un cluster de données.
+    fn init_directory_cluster(
+        &mut self,
+        current_cluster: u32,
+        parent_cluster: u32,
+    ) -> Result<(), FsError> {
         let cluster_size = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
         let mut data = vec![0u8; cluster_size];
 
         data[0..11].copy_from_slice(b".          ");
-        data[11] = 0x10;
+        data[11] = ATTR_DIRECTORY;
+        self.stamp_new_entry(&mut data[0..32]);
         data[20..22].copy_from_slice(&((current_cluster >> 16) as u16).to_le_bytes());
         data[26..28].copy_from_slice(&(current_cluster as u16).to_le_bytes());
 
         data[32..43].copy_from_slice(b"..         ");
-        data[43] = 0x10;
+        data[43] = ATTR_DIRECTORY;
+        self.stamp_new_entry(&mut data[32..64]);
 
         let parent_val = if parent_cluster == self.root_cluster {
             0
@@ -371,9 +2420,10 @@ impl Fat32FileSystem {
         data[52..54].copy_from_slice(&((parent_val >> 16) as u16).to_le_bytes());
         data[58..60].copy_from_slice(&(parent_val as u16).to_le_bytes());
 
-        let start_sector = self.data_sector + (current_cluster - 2) * self.sectors_per_cluster;
+        let start_sector = self.cluster_to_sector(current_cluster)?;
         let offset_in_disk = (start_sector * self.bytes_per_sector) as usize;
         self.disk[offset_in_disk..offset_in_disk + cluster_size].copy_from_slice(&data);
+        Ok(())
     }
     /// Crée un nouveau dossier  sur le disque.
     ///
@@ -382,9 +2432,13 @@ impl Fat32FileSystem {
     /// - Initialise ce cluster avec les entrées `.` et `..`.
     /// - Ajoute une entrée de type `0x10` (Directory) dans le répertoire parent.
     ///
+    /// Si une étape échoue après l’allocation, le cluster est libéré dans la FAT.
+    ///
     /// # Errors
     /// Échoue si le disque est plein ou si le chemin parent n'existe pas.
     pub fn mkdir(&mut self, parent_path: &str, folder_name: &str) -> Result<(), String> {
+        self.ensure_writable()?;
+
         let parent_cluster = if parent_path.is_empty() || parent_path == "/" {
             self.root_cluster
         } else {
@@ -393,25 +2447,48 @@ impl Fat32FileSystem {
                 .ok_or_else(|| "Parent path not found".to_string())?
         };
 
-        let new_folder_cluster = self.allocate_cluster()?;
+        let short_name = Self::format_to_8_3(folder_name)?;
+
+        self.mkdir_in(parent_cluster, short_name, &[])?;
+        Ok(())
+    }
 
-        self.init_directory_cluster(new_folder_cluster, parent_cluster);
+    /// Crée un dossier nommé `short_name` (précédé des fragments `lfn`) dans le répertoire
+    /// `parent_cluster`.
+    ///
+    /// Retourne le cluster du nouveau dossier ; il est libéré si l’inscription échoue.
+    fn mkdir_in(
+        &mut self,
+        parent_cluster: u32,
+        short_name: [u8; 11],
+        lfn: &[[u8; 32]],
+    ) -> Result<u32, FsError> {
+        let new_folder_cluster = self.allocate_cluster(self.allocation_hint())?;
+
+        if let Err(error) =
+            self.link_new_directory(new_folder_cluster, parent_cluster, short_name, lfn)
+        {
+            self.write_fat_entry(new_folder_cluster, FAT_FREE);
+            return Err(error);
+        }
 
-        let short_name = Self::format_to_8_3(folder_name)?;
+        Ok(new_folder_cluster)
+    }
 
-        let idx = {
-            let cluster_data = self.read_cluster(parent_cluster);
-            cluster_data
-                .chunks_exact(32)
-                .enumerate()
-                .find(|(_, chunk)| chunk[0] == 0x00 || chunk[0] == 0xE5)
-                .map(|(i, _)| i)
-                .ok_or_else(|| "No space in parent directory".to_string())?
-        };
+    /// Initialise le cluster d’un nouveau dossier et l’inscrit dans son répertoire parent.
+    fn link_new_directory(
+        &mut self,
+        new_folder_cluster: u32,
+        parent_cluster: u32,
+        short_name: [u8; 11],
+        lfn: &[[u8; 32]],
+    ) -> Result<(), FsError> {
+        self.init_directory_cluster(new_folder_cluster, parent_cluster)?;
 
         let mut new_entry = [0u8; 32];
         new_entry[0..11].copy_from_slice(&short_name);
-        new_entry[11] = 0x10;
+        new_entry[11] = ATTR_DIRECTORY;
+        self.stamp_new_entry(&mut new_entry);
 
         let high = (new_folder_cluster >> 16) as u16;
         let low = (new_folder_cluster & 0xFFFF) as u16;
@@ -419,97 +2496,265 @@ impl Fat32FileSystem {
         new_entry[26..28].copy_from_slice(&low.to_le_bytes());
         new_entry[28..32].copy_from_slice(&0u32.to_le_bytes());
 
-        self.write_directory_entry(parent_cluster, idx, new_entry);
+        self.write_entry_run(parent_cluster, lfn, &new_entry)
+            .map(|_| ())
+    }
 
-        Ok(())
+    /// Écrit `data` dans le fichier désigné par `path`, en le créant s’il n’existe pas.
+    ///
+    /// Le contenu existant est remplacé : la chaîne est agrandie ou raccourcie selon la
+    /// taille des données et les clusters en surplus sont libérés. Seule l’entrée du fichier
+    /// est créée, le répertoire parent doit exister. Un nom qui n’est pas un nom 8.3 en
+    /// majuscules reçoit un nom long (voir [`name_entries`]), de sorte que le chemin fourni
+    /// désigne ensuite le fichier. Les dates de modification et d’accès sont mises à jour
+    /// via [`Self::touch_mtime`].
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`] pour le répertoire parent
+    /// - [`FsError::NotAFile`] si le chemin désigne un répertoire
+    /// - [`FsError::NotADirectory`] si le parent est un fichier
+    /// - [`FsError::InvalidName`] si le nom n’est pas un nom valide
+    /// - [`FsError::AlreadyExists`] si le parent contient ce nom avec une autre casse
+    /// - [`FsError::DiskFull`] si la chaîne ne peut pas être agrandie (le fichier reste inchangé)
+    pub fn write_file(
+        &mut self,
+        path: &str,
+        current_cluster: Option<u32>,
+        data: &[u8],
+    ) -> Result<(), FsError> {
+        self.ensure_writable()?;
+
+        let location = self.open_for_write(path, current_cluster)?;
+        self.overwrite_at(&location, data)
     }
 
-    /// Met à jour la taille d'un fichier dans son entrée de répertoire parent.
+    /// Retourne l’emplacement de l’entrée du fichier `path`, créée si elle n’existe pas.
     ///
     /// # Errors
-    /// Retourne une erreur si le fichier est introuvable ou si le format du nom est invalide.
-    fn update_file_size(&mut self, path: &str, new_size: u32) -> Result<(), String> {
-        let (parent_path, filename) = if let Some(pos) = path.rfind('/') {
-            (&path[..pos], &path[pos + 1..])
-        } else {
-            ("", path)
-        };
+    /// Erreurs de [`Self::write_file`] concernant le chemin et le nom
+    fn open_for_write(
+        &mut self,
+        path: &str,
+        current_cluster: Option<u32>,
+    ) -> Result<EntryLocation, FsError> {
+        match self.locate_entry(path, current_cluster) {
+            Err(FsError::NotFound) => {
+                let (parent_cluster, name) = self.split_parent(path, current_cluster)?;
+                let (short_name, lfn) = self.entry_names(parent_cluster, name, None)?;
+                self.create_file_in(parent_cluster, short_name, &lfn)
+            }
+            result => result,
+        }
+    }
 
-        let parent_cluster = if parent_path.is_empty() || parent_path == "/" {
-            self.root_cluster
-        } else {
-            self.parse_path(parent_path, None)
-                .map(|f| f.start_cluster)
-                .unwrap_or(self.root_cluster)
-        };
+    /// Ouvre un fichier en écriture séquentielle, en le créant ou en le vidant.
+    ///
+    /// Les données passées à [`FileWriter::write`] sont accumulées par cluster entier ;
+    /// [`FileWriter::finish`] écrit le reste et doit être appelé pour ne rien perdre.
+    /// Le chemin suit les règles de [`Self::write_file`] (relatif à la racine).
+    ///
+    /// # Errors
+    /// Erreurs de [`Self::write_file`]
+    pub fn create_writer(&mut self, path: &str) -> Result<FileWriter<'_>, FsError> {
+        self.ensure_writable()?;
+
+        let location = self.open_for_write(path, None)?;
+        self.overwrite_at(&location, &[])?;
+        let cluster_bytes = (self.bytes_per_sector * self.sectors_per_cluster) as usize;
+
+        Ok(FileWriter {
+            fs: self,
+            location,
+            buffer: Vec::with_capacity(cluster_bytes),
+            cluster_bytes,
+        })
+    }
+
+    /// Sépare `path` en cluster du répertoire parent (qui doit exister) et dernier composant.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`] pour le répertoire parent
+    /// - [`FsError::NotADirectory`] si le parent est un fichier
+    fn split_parent<'p>(
+        &self,
+        path: &'p str,
+        current_cluster: Option<u32>,
+    ) -> Result<(u32, &'p str), FsError> {
+        let (parent_path, name) = path.rsplit_once('/').unwrap_or(("", path));
+
+        if parent_path.is_empty() {
+            let cluster = if path.starts_with('/') {
+                self.root_cluster
+            } else {
+                current_cluster.unwrap_or(self.root_cluster)
+            };
+            return Ok((cluster, name));
+        }
 
-        let short_name = Self::format_to_8_3(filename).map_err(|e| e.to_string())?;
+        let parent = self.resolve(parent_path, current_cluster)?.file;
+        if !parent.is_directory {
+            return Err(FsError::NotADirectory);
+        }
 
-        let entry_idx = {
-            let cluster_data = self.read_cluster(parent_cluster);
-            cluster_data
-                .chunks_exact(32)
-                .enumerate()
-                .find(|(_, chunk)| chunk[0..11] == short_name)
-                .map(|(i, _)| i)
-                .ok_or_else(|| "Entrée introuvable pour mise à jour".to_string())?
-        };
+        Ok((self.dir_cluster(&parent), name))
+    }
+
+    /// Retourne le cluster d’un répertoire résolu : `..` vers la racine pointe sur le cluster 0.
+    fn dir_cluster(&self, dir: &FileInfo) -> u32 {
+        match dir.start_cluster {
+            0 => self.root_cluster,
+            cluster => cluster,
+        }
+    }
+
+    /// Cherche dans un répertoire l’entrée dont le nom correspond au nom court donné.
+    fn find_child(&self, cluster: u32, short_name: [u8; 11]) -> Option<FileInfo> {
+        list_directory_entries(self, cluster)
+            .into_iter()
+            .filter(|f| !f.is_dot_entry)
+            .find(|f| to_short_name(&f.name) == Some(short_name))
+    }
+
+    /// Remplace le contenu du fichier dont l’entrée se trouve à `location`.
+    ///
+    /// Un contenu vide libère toute la chaîne et remet le cluster de départ à 0.
+    fn overwrite_at(&mut self, location: &EntryLocation, data: &[u8]) -> Result<(), FsError> {
+        let original = self.read_entry_at(location.cluster, location.offset_in_cluster)?;
+        let entry = FatDir::new(&original);
+
+        if EntryKind::classify(entry.attr) != EntryKind::File {
+            return Err(FsError::NotAFile);
+        }
 
-        let start_sector = self.data_sector + (parent_cluster - 2) * self.sectors_per_cluster;
-        let byte_offset = (entry_idx * 32) + 28;
-        let sector_offset = byte_offset as u32 / self.bytes_per_sector;
-        let offset_in_sector = byte_offset % self.bytes_per_sector as usize;
+        // La chaîne existante est réutilisée depuis le début, puis agrandie si besoin
+        let reset = FileInfo::new(String::new(), false, 0, entry.first_cluster());
+        self.write_entry_at(
+            location.cluster,
+            location.offset_in_cluster,
+            &file_info_to_entry(&reset, &original),
+        )?;
+        if let Err(error) = self.append_at(location, data) {
+            self.write_entry_at(location.cluster, location.offset_in_cluster, &original)?;
+            return Err(error);
+        }
 
-        let final_offset =
-            ((start_sector + sector_offset) * self.bytes_per_sector) as usize + offset_in_sector;
+        let cluster_bytes = (self.bytes_per_sector * self.sectors_per_cluster) as usize;
+        let chain = self.cluster_chain(entry.first_cluster());
+        let keep = data.len().div_ceil(cluster_bytes);
 
-        self.disk[final_offset..final_offset + 4].copy_from_slice(&new_size.to_le_bytes());
+        if chain.len() > keep {
+            for &cluster in &chain[keep..] {
+                self.release_cluster(cluster);
+            }
+            match keep {
+                0 => {
+                    let mut emptied = FatDir::new(
+                        &self.read_entry_at(location.cluster, location.offset_in_cluster)?,
+                    );
+                    emptied.set_first_cluster(0);
+                    self.write_entry_at(
+                        location.cluster,
+                        location.offset_in_cluster,
+                        &emptied.to_bytes(),
+                    )?;
+                }
+                _ => self.write_fat_entry(chain[keep - 1], FAT_EOC_MARK),
+            }
+        }
 
         Ok(())
     }
 
-    /// Écrit des données dans un fichier existant.
+    /// Ajoute des données à la fin d’un fichier existant.
     ///
-    /// Actuellement, cette implémentation supporte l'écriture de données ne dépassant pas la taille d'un seul cluster (limite du premier cluster alloué).
-    ///
-    /// # Détails techniques
-    /// * La fonction limite `bytes_to_write` à la taille maximale d'un cluster pour éviter tout débordement sur les clusters adjacents non liés.
-    /// * Après l'écriture des données brutes, elle appelle automatiquement [`Self::update_file_size`] pour synchroniser les métadonnées du fichier.
+    /// La chaîne est agrandie si nécessaire, en cherchant les nouveaux clusters à partir
+    /// du cluster suivant la fin du fichier pour limiter la fragmentation.
     ///
     /// # Errors
-    /// * Retourne une erreur si le chemin pointe vers un répertoire.
-    /// * Retourne une erreur si le fichier n'existe pas (le fichier doit être créé via `create_file` au préalable).
-    ///
-    /// # Panics
-    /// Peut paniquer si le calcul d'offset global dépasse les limites du disque monté.
-    pub fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), String> {
-        let file_info = self
-            .parse_path(path, None)
-            .ok_or_else(|| "Fichier non trouvé".to_string())?;
+    /// - Erreurs de [`Self::resolve`]
+    /// - [`FsError::NotAFile`] si le chemin désigne un répertoire
+    /// - [`FsError::DiskFull`] si la chaîne ne peut pas être agrandie (le fichier reste inchangé)
+    /// - [`FsError::FileTooLarge`] si la nouvelle taille dépasse la limite FAT32 de
+    ///   `u32::MAX` octets (le fichier reste inchangé)
+    pub fn append_file(&mut self, path: &str, data: &[u8]) -> Result<(), FsError> {
+        self.ensure_writable()?;
+
+        let location = self.locate_entry(path, None)?;
+        self.append_at(&location, data)
+    }
 
-        if file_info.is_directory {
-            return Err("Impossible d'écrire dans un répertoire".to_string());
+    /// Ajoute des données à la fin du fichier dont l’entrée se trouve à `location`.
+    fn append_at(&mut self, location: &EntryLocation, data: &[u8]) -> Result<(), FsError> {
+        let mut entry =
+            FatDir::new(&self.read_entry_at(location.cluster, location.offset_in_cluster)?);
+
+        if EntryKind::classify(entry.attr) != EntryKind::File {
+            return Err(FsError::NotAFile);
         }
 
-        let cluster_size = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
-        let bytes_to_write = data.len().min(cluster_size);
+        let cluster_bytes = (self.bytes_per_sector * self.sectors_per_cluster) as usize;
+        let old_size = entry.size as usize;
+        let new_size = old_size + data.len();
+        if new_size > u32::MAX as usize {
+            return Err(FsError::FileTooLarge);
+        }
+
+        let mut chain = self.cluster_chain(entry.first_cluster());
+        let old_len = chain.len();
 
-        let start_sector =
-            self.data_sector + (file_info.start_cluster - 2) * self.sectors_per_cluster;
-        let global_offset = (start_sector * self.bytes_per_sector) as usize;
+        while chain.len() * cluster_bytes < new_size {
+            let hint = chain
+                .last()
+                .map_or(self.allocation_hint(), |&last| self.extension_hint(last));
 
-        self.disk[global_offset..global_offset + bytes_to_write]
-            .copy_from_slice(&data[..bytes_to_write]);
+            let cluster = match self.allocate_cluster(hint) {
+                Ok(cluster) => cluster,
+                Err(error) => {
+                    // Annule l’agrandissement partiel de la chaîne
+                    for &cluster in &chain[old_len..] {
+                        self.write_fat_entry(cluster, FAT_FREE);
+                    }
+                    if let Some(&last) = chain[..old_len].last() {
+                        self.write_fat_entry(last, FAT_EOC_MARK);
+                    }
+                    return Err(error);
+                }
+            };
 
-        self.update_file_size(path, bytes_to_write as u32)?;
+            match chain.last() {
+                Some(&last) => self.write_fat_entry(last, cluster),
+                None => entry.set_first_cluster(cluster),
+            }
+            chain.push(cluster);
+        }
 
-        Ok(())
+        let mut written = 0;
+        while written < data.len() {
+            let position = old_size + written;
+            let cluster = chain[position / cluster_bytes];
+            let in_cluster = position % cluster_bytes;
+            let count = (cluster_bytes - in_cluster).min(data.len() - written);
+
+            let start_sector = self.cluster_to_sector(cluster)?;
+            let offset = (start_sector * self.bytes_per_sector) as usize + in_cluster;
+            self.disk[offset..offset + count].copy_from_slice(&data[written..written + count]);
+
+            written += count;
+        }
+
+        entry.size = new_size as u32;
+        self.write_entry_at(
+            location.cluster,
+            location.offset_in_cluster,
+            &entry.to_bytes(),
+        )?;
+        self.touch_mtime(location)
     }
 }
 
 /// Représente une entrée de répertoire FAT32 standard (32 octets).
 /// Cette structure correspond au layout sur disque d’une entrée FAT (format 8.3).
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct FatDir {
     /// Nom court (8.3) encodé sur 11 octets.
     pub name: [u8; 11],
@@ -517,9 +2762,27 @@ pub struct FatDir {
     /// Attributs FAT (directory, volume label, read-only, etc.).
     pub attr: u8,
 
+    /// Centièmes de seconde de l’heure de création (0 à 199).
+    pub create_time_tenth: u8,
+
+    /// Heure de création (format FAT).
+    pub create_time: u16,
+
+    /// Date de création (format FAT).
+    pub create_date: u16,
+
+    /// Date du dernier accès (format FAT).
+    pub access_date: u16,
+
     /// Partie haute du cluster de départ (FAT32).
     pub first_cluster_high: u16,
 
+    /// Heure de dernière modification (format FAT).
+    pub write_time: u16,
+
+    /// Date de dernière modification (format FAT).
+    pub write_date: u16,
+
     /// Partie basse du cluster de départ.
     pub first_cluster_low: u16,
 
@@ -535,8 +2798,20 @@ pub enum DirOffsets {
     Name = 0,
     /// Attributs.
     Attr = 11,
+    /// Centièmes de seconde de l’heure de création.
+    CrtTimeTenth = 13,
+    /// Heure de création.
+    CrtTime = 14,
+    /// Date de création.
+    CrtDate = 16,
+    /// Date du dernier accès.
+    LstAccDate = 18,
     /// Partie haute du cluster de départ.
     FstClusHI = 20,
+    /// Heure de dernière modification.
+    WrtTime = 22,
+    /// Date de dernière modification.
+    WrtDate = 24,
     /// Partie basse du cluster de départ.
     FstClusLO = 26,
     /// Taille du fichier.
@@ -556,6 +2831,50 @@ impl FatDir {
         u32::from_le_bytes(data[o..o + 4].try_into().unwrap())
     }
 
+    /// Écrit un entier 16 bits little-endian dans une entrée FAT.
+    fn write_u16(data: &mut [u8], offset: DirOffsets, value: u16) {
+        let o = offset as usize;
+        data[o..o + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Écrit un entier 32 bits little-endian dans une entrée FAT.
+    fn write_u32(data: &mut [u8], offset: DirOffsets, value: u32) {
+        let o = offset as usize;
+        data[o..o + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Assemble les parties haute et basse du cluster de départ.
+    pub fn first_cluster(&self) -> u32 {
+        ((self.first_cluster_high as u32) << 16) | (self.first_cluster_low as u32)
+    }
+
+    /// Répartit un cluster de départ entre les parties haute et basse.
+    pub fn set_first_cluster(&mut self, cluster: u32) {
+        self.first_cluster_high = (cluster >> 16) as u16;
+        self.first_cluster_low = (cluster & 0xFFFF) as u16;
+    }
+
+    /// Sérialise l’entrée dans son format sur disque (32 octets).
+    ///
+    /// Inverse de [`FatDir::new`] : l’octet réservé (`NTRes`, offset 12) est mis à zéro.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut data = [0u8; 32];
+
+        data[DirOffsets::Name as usize..DirOffsets::Name as usize + 11].copy_from_slice(&self.name);
+        data[DirOffsets::Attr as usize] = self.attr;
+        data[DirOffsets::CrtTimeTenth as usize] = self.create_time_tenth;
+        Self::write_u16(&mut data, DirOffsets::CrtTime, self.create_time);
+        Self::write_u16(&mut data, DirOffsets::CrtDate, self.create_date);
+        Self::write_u16(&mut data, DirOffsets::LstAccDate, self.access_date);
+        Self::write_u16(&mut data, DirOffsets::FstClusHI, self.first_cluster_high);
+        Self::write_u16(&mut data, DirOffsets::WrtTime, self.write_time);
+        Self::write_u16(&mut data, DirOffsets::WrtDate, self.write_date);
+        Self::write_u16(&mut data, DirOffsets::FstClusLO, self.first_cluster_low);
+        Self::write_u32(&mut data, DirOffsets::FileSize, self.size);
+
+        data
+    }
+
     /// Construit une entrée [`FatDir`] à partir de 32 octets bruts.
     ///
     /// # Panics
@@ -566,14 +2885,26 @@ impl FatDir {
             .unwrap();
 
         let attr = data[DirOffsets::Attr as usize];
+        let create_time_tenth = data[DirOffsets::CrtTimeTenth as usize];
+        let create_time = Self::read_u16(data, DirOffsets::CrtTime);
+        let create_date = Self::read_u16(data, DirOffsets::CrtDate);
+        let access_date = Self::read_u16(data, DirOffsets::LstAccDate);
         let first_cluster_high = Self::read_u16(data, DirOffsets::FstClusHI);
+        let write_time = Self::read_u16(data, DirOffsets::WrtTime);
+        let write_date = Self::read_u16(data, DirOffsets::WrtDate);
         let first_cluster_low = Self::read_u16(data, DirOffsets::FstClusLO);
         let size = Self::read_u32(data, DirOffsets::FileSize);
 
         FatDir {
             name,
             attr,
+            create_time_tenth,
+            create_time,
+            create_date,
+            access_date,
             first_cluster_high,
+            write_time,
+            write_date,
             first_cluster_low,
             size,
         }
@@ -582,6 +2913,7 @@ impl FatDir {
 
 /// Représente une entrée Long File Name (LFN).
 /// Les entrées LFN précèdent toujours l’entrée FAT classique correspondante et contiennent le nom en UTF-16.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LongFileName {
     /// Numéro de séquence (ordre inverse).
     pub seq_num: u8,
@@ -661,6 +2993,27 @@ impl LongFileName {
             name_3,
         }
     }
+
+    /// Sérialise l’entrée LFN dans son format sur disque (32 octets).
+    ///
+    /// Inverse de [`LongFileName::new`] : l’attribut est toujours `0x0F`,
+    /// le type et le mot réservé sont mis à zéro.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut data = [0u8; 32];
+
+        data[LfnOffsets::Ord as usize] = self.seq_num;
+        data[LfnOffsets::Name1 as usize..LfnOffsets::Name1 as usize + 10]
+            .copy_from_slice(&self.name_1);
+        data[LfnOffsets::Attr as usize] = ATTR_LFN;
+        data[LfnOffsets::LType as usize] = 0;
+        data[LfnOffsets::ChkSum as usize] = self.chksum;
+        data[LfnOffsets::Name2 as usize..LfnOffsets::Name2 as usize + 12]
+            .copy_from_slice(&self.name_2);
+        data[LfnOffsets::Name3 as usize..LfnOffsets::Name3 as usize + 4]
+            .copy_from_slice(&self.name_3);
+
+        data
+    }
 }
 
 /// Représente un fichier ou un répertoire au niveau logique.
@@ -678,18 +3031,265 @@ pub struct FileInfo {
 
     /// Cluster de départ.
     pub start_cluster: u32,
+
+    /// Date de création.
+    pub created: FatTimestamp,
+
+    /// Date de dernière modification.
+    pub modified: FatTimestamp,
+
+    /// Indique si l’entrée est l’étiquette du volume (listing brut uniquement).
+    pub is_volume_label: bool,
+
+    /// Indique que le nom long était illisible (UTF-16 invalide) et que le nom
+    /// court a été utilisé à la place.
+    pub lfn_fallback: bool,
+
+    /// Indique une entrée spéciale `.` ou `..`, détectée sur les octets bruts du nom court
+    /// (un nom long ne peut donc pas la simuler).
+    pub is_dot_entry: bool,
+
+    /// Octet d’attributs FAT de l’entrée (`ATTR_*`).
+    pub attributes: u8,
 }
 
 impl FileInfo {
     /// Construit un nouvel objet [`FileInfo`].
+    ///
+    /// Les dates sont initialisées à l’époque FAT ; les attributs se limitent à
+    /// [`ATTR_DIRECTORY`] pour un répertoire.
     pub fn new(name: String, is_directory: bool, size: u32, start_cluster: u32) -> FileInfo {
         FileInfo {
             name,
             is_directory,
             size,
             start_cluster,
+            created: FatTimestamp::EPOCH,
+            modified: FatTimestamp::EPOCH,
+            is_volume_label: false,
+            lfn_fallback: false,
+            is_dot_entry: false,
+            attributes: if is_directory { ATTR_DIRECTORY } else { 0 },
+        }
+    }
+
+    /// Retourne l’extension du nom (après le dernier `.`).
+    ///
+    /// Un nom commençant par `.` sans autre point (`.hidden`) n’a pas d’extension,
+    /// un nom terminé par `.` a une extension vide.
+    pub fn extension(&self) -> Option<&str> {
+        self.split_extension().1
+    }
+
+    /// Retourne le nom sans son extension.
+    pub fn stem(&self) -> &str {
+        self.split_extension().0
+    }
+
+    /// Sépare le nom sur son dernier `.`, en ignorant un point initial.
+    fn split_extension(&self) -> (&str, Option<&str>) {
+        match self.name.rfind('.') {
+            Some(0) | None => (&self.name, None),
+            Some(dot) => (&self.name[..dot], Some(&self.name[dot + 1..])),
+        }
+    }
+}
+
+/// Résultat de la résolution d’un chemin par [`Fat32FileSystem::resolve`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedPath {
+    /// Entrée désignée par le chemin.
+    pub file: FileInfo,
+
+    /// Cluster du répertoire contenant l’entrée.
+    pub parent_cluster: u32,
+
+    /// Composantes du chemin parcourues (la dernière est le nom de l’entrée).
+    pub components: Vec<String>,
+}
+
+/// Emplacement d’une entrée de répertoire sur le disque.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryLocation {
+    /// Cluster du répertoire contenant l’entrée.
+    pub cluster: u32,
+
+    /// Offset de l’entrée (en octets) dans ce cluster.
+    pub offset_in_cluster: usize,
+}
+
+/// Itérateur sur les clusters d’un fichier, créé par [`Fat32FileSystem::file_clusters`].
+///
+/// La chaîne est suivie dans la FAT au fil de l’itération : seuls les clusters parcourus
+/// sont lus. L’itération s’arrête à la fin de la taille déclarée, sur une fin de chaîne
+/// (marqueur de fin, cluster libre ou déjà visité) ou après la première erreur, retournée
+/// comme élément (cluster invalide, secteur illisible).
+#[derive(Debug, Clone)]
+pub struct ClusterReader<'a> {
+    fs: &'a Fat32FileSystem,
+    cluster: u32,
+    visited: BTreeSet<u32>,
+    fat_cache: Option<(u32, Vec<u8>)>,
+    remaining: usize,
+}
+
+impl Iterator for ClusterReader<'_> {
+    type Item = Result<Vec<u8>, FsError>;
+
+    fn next(&mut self) -> Option<Result<Vec<u8>, FsError>> {
+        let cluster = self.cluster;
+        if self.remaining == 0
+            || cluster == FAT_FREE
+            || is_end_of_chain(cluster)
+            || !self.visited.insert(cluster)
+        {
+            return None;
+        }
+
+        let cluster_bytes = (self.fs.bytes_per_sector * self.fs.sectors_per_cluster) as usize;
+        let length = self.remaining.min(cluster_bytes);
+
+        let data = self
+            .fs
+            .cluster_to_sector(cluster)
+            .and_then(|sector| self.fs.read_sectors(sector, self.fs.sectors_per_cluster));
+        let Ok(mut data) = data else {
+            self.remaining = 0;
+            return Some(data);
+        };
+
+        data.truncate(length);
+        self.remaining -= length;
+        if self.remaining > 0 {
+            self.cluster = self.fs.read_fat_entry_cached(cluster, &mut self.fat_cache);
+        }
+        Some(Ok(data))
+    }
+}
+
+/// Écriture séquentielle d’un fichier, créée par [`Fat32FileSystem::create_writer`].
+///
+/// Les données sont écrites sur le disque par clusters entiers : la chaîne est agrandie
+/// au fil de l’eau et la taille de l’entrée mise à jour à chaque cluster écrit. Un
+/// `FileWriter` abandonné sans [`Self::finish`] perd le contenu encore en mémoire.
+pub struct FileWriter<'a> {
+    fs: &'a mut Fat32FileSystem,
+    location: EntryLocation,
+    buffer: Vec<u8>,
+    cluster_bytes: usize,
+}
+
+impl FileWriter<'_> {
+    /// Ajoute `buf` au fichier, en écrivant chaque cluster dès qu’il est complet.
+    ///
+    /// # Errors
+    /// - [`FsError::DiskFull`] si la chaîne ne peut pas être agrandie
+    pub fn write(&mut self, buf: &[u8]) -> Result<(), FsError> {
+        let mut rest = buf;
+
+        while !rest.is_empty() {
+            let count = (self.cluster_bytes - self.buffer.len()).min(rest.len());
+            self.buffer.extend_from_slice(&rest[..count]);
+            rest = &rest[count..];
+
+            if self.buffer.len() == self.cluster_bytes {
+                self.fs.append_at(&self.location, &self.buffer)?;
+                self.buffer.clear();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Écrit les données restantes et termine l’écriture.
+    ///
+    /// # Errors
+    /// - [`FsError::DiskFull`] si la chaîne ne peut pas être agrandie
+    pub fn finish(mut self) -> Result<(), FsError> {
+        if !self.buffer.is_empty() {
+            self.fs.append_at(&self.location, &self.buffer)?;
+            self.buffer.clear();
         }
+
+        Ok(())
+    }
+}
+
+/// Répertoire résolu une seule fois, créé par [`Fat32FileSystem::open_dir`].
+///
+/// Chaque appel relit le répertoire sur le disque : le handle ne met en cache que son
+/// cluster, les entrées reflètent donc les modifications faites entre deux appels.
+#[derive(Debug, Clone, Copy)]
+pub struct DirHandle<'a> {
+    fs: &'a Fat32FileSystem,
+    cluster: u32,
+}
+
+impl<'a> DirHandle<'a> {
+    /// Retourne le premier cluster du répertoire.
+    pub fn cluster(&self) -> u32 {
+        self.cluster
+    }
+
+    /// Retourne les entrées du répertoire, sans `.` ni `..`.
+    pub fn entries(&self) -> Vec<FileInfo> {
+        self.iter().collect()
+    }
+
+    /// Parcourt les entrées du répertoire, sans `.` ni `..`.
+    ///
+    /// Comme [`Fat32FileSystem::dir_iter`], les clusters ne sont lus qu’au fil de l’itération.
+    pub fn iter(&self) -> impl Iterator<Item = FileInfo> + use<'a> {
+        self.fs.dir_iter(self.cluster).filter(|f| !f.is_dot_entry)
+    }
+
+    /// Indique si le répertoire contient une entrée nommée `name`.
+    ///
+    /// La comparaison est celle de [`Fat32FileSystem::resolve`] (sensible à la casse).
+    pub fn exists(&self, name: &str) -> bool {
+        self.iter().any(|f| f.name == name)
+    }
+}
+
+/// Retourne le cluster de départ d’une entrée de répertoire.
+///
+/// Une entrée de répertoire pointant vers le cluster 0 (cas du `..` d’un sous-répertoire
+/// de premier niveau) désigne la racine : `root_cluster` est alors retourné.
+fn entry_start_cluster(entry: &FatDir, root_cluster: u32) -> u32 {
+    match entry.first_cluster() {
+        0 => root_cluster,
+        cluster => cluster,
+    }
+}
+
+/// Reporte sur une entrée brute les champs d’un [`FileInfo`] indépendants du nom.
+///
+/// Le cluster de départ, la taille et les bits répertoire et étiquette sont mis à jour ;
+/// le nom, les horodatages, les autres attributs et l’octet réservé de `original` sont
+/// conservés. Le cluster d’une entrée `.`/`..` n’est pas modifié (`0` y désigne la racine).
+fn file_info_to_entry(info: &FileInfo, original: &[u8; 32]) -> [u8; 32] {
+    let mut entry = *original;
+
+    let mut attr = original[DirOffsets::Attr as usize] & !(ATTR_DIRECTORY | ATTR_VOLUME_ID);
+    if info.is_directory {
+        attr |= ATTR_DIRECTORY;
+    }
+    if info.is_volume_label {
+        attr |= ATTR_VOLUME_ID;
+    }
+    entry[DirOffsets::Attr as usize] = attr;
+
+    if !info.is_dot_entry {
+        FatDir::write_u16(
+            &mut entry,
+            DirOffsets::FstClusHI,
+            (info.start_cluster >> 16) as u16,
+        );
+        FatDir::write_u16(&mut entry, DirOffsets::FstClusLO, info.start_cluster as u16);
     }
+    FatDir::write_u32(&mut entry, DirOffsets::FileSize, info.size);
+
+    entry
 }
 
 /// Calcule le checksum d’un nom court (8.3)
@@ -710,39 +3310,202 @@ fn lfn_checksum(short_name: &[u8; 11]) -> u8 {
 ///
 /// - Supprime les espaces de padding
 /// - Gère l’extension
+/// - Décode les octets non ASCII selon la page de code OEM 437 (voir [`cp437_char`])
 /// - Retourne un nom lisible (`FILE.TXT`)
 fn short_name_to_string(name11: &[u8; 11]) -> String {
-    let name_part = &name11[0..8];
-    let ext_part = &name11[8..11];
+    short_name_cow(name11).into_owned()
+}
 
-    let name_str = {
-        let mut end = name_part.len();
-        while end > 0 && name_part[end - 1] == b' ' {
+/// Décode un nom court FAT (8.3) sans allocation lorsque c’est possible
+///
+/// Un nom ASCII sans extension est emprunté directement dans l’entrée (padding retiré) ;
+/// un nom avec extension ou non ASCII est reconstruit dans une `String`, octet par octet
+/// pour qu’un octet hors ASCII ne fasse pas disparaître tout le nom. Un premier octet
+/// `0x05` désigne, comme le prévoit la spécification, le caractère `0xE5`
+fn short_name_cow(name11: &[u8; 11]) -> Cow<'_, str> {
+    let trim = |part: &'_ [u8]| -> usize {
+        let mut end = part.len();
+        while end > 0 && part[end - 1] == b' ' {
             end -= 1;
         }
-        core::str::from_utf8(&name_part[..end])
-            .unwrap_or("")
-            .to_string()
+        end
     };
 
-    let ext_str = {
-        let mut end = ext_part.len();
-        while end > 0 && ext_part[end - 1] == b' ' {
-            end -= 1;
+    let name_part = &name11[0..trim(&name11[0..8])];
+    let ext_part = &name11[8..8 + trim(&name11[8..11])];
+
+    if ext_part.is_empty() && name_part.is_ascii() && name_part.first() != Some(&0x05) {
+        return Cow::Borrowed(core::str::from_utf8(name_part).unwrap());
+    }
+
+    let decode = |part: &[u8]| -> String { part.iter().map(|&byte| cp437_char(byte)).collect() };
+
+    let mut name = decode(name_part);
+    if name_part.first() == Some(&0x05) {
+        name.replace_range(..1, &cp437_char(0xE5).to_string());
+    }
+
+    if !ext_part.is_empty() {
+        name.push('.');
+        name.push_str(&decode(ext_part));
+    }
+
+    Cow::Owned(name)
+}
+
+/// Moitié haute (`0x80..=0xFF`) de la page de code OEM 437, utilisée par défaut pour
+/// les noms courts.
+const CP437_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', //
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', //
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', //
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐', //
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧', //
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀', //
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', //
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{A0}',
+];
+
+/// Décode un octet de nom court selon la page de code OEM 437 (ASCII en dessous de `0x80`).
+fn cp437_char(byte: u8) -> char {
+    match byte {
+        0x00..=0x7F => byte as char,
+        _ => CP437_HIGH[(byte - 0x80) as usize],
+    }
+}
+
+/// Caractères spéciaux autorisés dans un nom court, en plus des lettres et chiffres.
+const SHORT_NAME_SPECIAL: &[u8] = b"$%'-_@~`!(){}^#&";
+
+/// Prépare les entrées de répertoire d’un nom.
+///
+/// Un nom 8.3 déjà en majuscules est écrit seul. Tout autre nom reçoit un alias court
+/// `BASE~N.EXT` absent de `taken` et ses fragments LFN, retournés dans l’ordre du disque
+/// (dernier fragment en premier). Retourne `None` si le nom est vide, réservé (`.`/`..`),
+/// contient un caractère interdit ou dépasse 255 caractères UTF-16.
+fn name_entries(name: &str, taken: &BTreeSet<[u8; 11]>) -> Option<([u8; 11], Vec<[u8; 32]>)> {
+    const FORBIDDEN: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || name
+            .chars()
+            .any(|c| c.is_control() || FORBIDDEN.contains(&c))
+    {
+        return None;
+    }
+
+    let exact = to_short_name(name).filter(|short| short_name_to_string(short) == name);
+    if let Some(short) = exact {
+        return Some((short, Vec::new()));
+    }
+
+    let (base, ext) = match name.rfind('.') {
+        Some(pos) if pos > 0 => (&name[..pos], &name[pos + 1..]),
+        _ => (name, ""),
+    };
+    let clean = |part: &str, max: usize| -> Vec<u8> {
+        part.chars()
+            .filter(|&c| c != ' ' && c != '.')
+            .map(|c| match c.to_ascii_uppercase() {
+                c if c.is_ascii_alphanumeric()
+                    || (c.is_ascii() && SHORT_NAME_SPECIAL.contains(&(c as u8))) =>
+                {
+                    c as u8
+                }
+                _ => b'_',
+            })
+            .take(max)
+            .collect()
+    };
+    let base = clean(base, 6);
+    let ext = clean(ext, 3);
+    if base.is_empty() {
+        return None;
+    }
+
+    let mut template = [b' '; 11];
+    template[8..8 + ext.len()].copy_from_slice(&ext);
+
+    let alias = (1..=999_999u32).find_map(|n| {
+        let suffix = format!("~{}", n);
+        let keep = base.len().min(8 - suffix.len());
+
+        let mut candidate = template;
+        candidate[..keep].copy_from_slice(&base[..keep]);
+        candidate[keep..keep + suffix.len()].copy_from_slice(suffix.as_bytes());
+        (!taken.contains(&candidate)).then_some(candidate)
+    })?;
+
+    let units: Vec<u16> = name.encode_utf16().collect();
+    if units.len() > 255 {
+        return None;
+    }
+
+    let checksum = lfn_checksum(&alias);
+    let count = units.len().div_ceil(13);
+    let mut entries = Vec::with_capacity(count);
+
+    for seq in (1..=count).rev() {
+        // Le nom est terminé par 0x0000 puis complété par 0xFFFF
+        let mut chars = [0xFFFFu16; 13];
+        for (i, slot) in chars.iter_mut().enumerate() {
+            let pos = (seq - 1) * 13 + i;
+            if pos < units.len() {
+                *slot = units[pos];
+            } else if pos == units.len() {
+                *slot = 0x0000;
+            }
         }
-        core::str::from_utf8(&ext_part[..end])
-            .unwrap_or("")
-            .to_string()
+        let bytes: Vec<u8> = chars.iter().flat_map(|c| c.to_le_bytes()).collect();
+
+        let lfn = LongFileName {
+            seq_num: if seq == count {
+                seq as u8 | 0x40
+            } else {
+                seq as u8
+            },
+            name_1: bytes[0..10].try_into().unwrap(),
+            attr: ATTR_LFN,
+            l_type: 0,
+            chksum: checksum,
+            name_2: bytes[10..22].try_into().unwrap(),
+            reserved_fch: 0,
+            name_3: bytes[22..26].try_into().unwrap(),
+        };
+        entries.push(lfn.to_bytes());
+    }
+
+    Some((alias, entries))
+}
+
+/// Convertit un nom en nom court FAT (8.3) exact, sans troncature
+///
+/// Inverse de [`short_name_to_string`] :
+/// - sépare le nom et l’extension sur le dernier `.`
+/// - convertit en majuscules et complète par des espaces
+/// - retourne `None` si le nom dépasse 8.3 ou contient un caractère interdit
+pub fn to_short_name(name: &str) -> Option<[u8; 11]> {
+    let (base, ext) = match name.rfind('.') {
+        Some(pos) => (&name[..pos], &name[pos + 1..]),
+        None => (name, ""),
     };
 
-    if ext_str.is_empty() {
-        name_str
-    } else {
-        let mut s = name_str;
-        s.push('.');
-        s.push_str(&ext_str);
-        s
+    if base.is_empty() || base.len() > 8 || ext.len() > 3 {
+        return None;
+    }
+
+    let is_valid = |b: &u8| b.is_ascii_alphanumeric() || SHORT_NAME_SPECIAL.contains(b);
+    if !base.bytes().all(|b| is_valid(&b)) || !ext.bytes().all(|b| is_valid(&b)) {
+        return None;
     }
+
+    let mut res = [b' '; 11];
+    res[..base.len()].copy_from_slice(base.to_ascii_uppercase().as_bytes());
+    res[8..8 + ext.len()].copy_from_slice(ext.to_ascii_uppercase().as_bytes());
+
+    Some(res)
 }
 
 /// Convertit un fragment de bytes LFN en UTF-16 (`u16`)
@@ -764,57 +3527,268 @@ type LfnFragments = Vec<(u8, Vec<u16>)>;
 /// Liste les entrées d’un répertoire FAT32.
 ///
 /// Cette fonction :
-/// - parcourt les entrées de 32 octets
+/// - parcourt les entrées de 32 octets de toute la chaîne de clusters du répertoire
 /// - gère les entrées supprimées et de fin
 /// - reconstruit les noms longs (LFN)
-/// - retourne une liste de [`FileInfo`]
+/// - retourne une liste de [`FileInfo`], sans l’étiquette de volume
 pub fn list_directory_entries(fs: &Fat32FileSystem, cluster_id: u32) -> Vec<FileInfo> {
-    let cluster_data = fs.read_cluster(cluster_id);
-    let mut results = Vec::new();
+    read_directory_entries(fs, cluster_id, false)
+}
 
-    let mut lfn_fragments: LfnFragments = Vec::new();
-    let mut expected_checksum: Option<u8> = None;
+/// Variante de [`list_directory_entries`] qui refuse un répertoire dont la chaîne de
+/// clusters est cyclique (clusters de répertoire croisés).
+///
+/// [`list_directory_entries`] s’arrête silencieusement au premier cluster répété ;
+/// cette fonction le signale pour que l’appelant ne prenne pas la liste pour complète.
+/// La chaîne n’est parcourue qu’une fois (voir [`DirIter::check_chain`]).
+///
+/// # Errors
+/// - [`FsError::CorruptChain`] si la chaîne du répertoire repasse par un cluster déjà visité
+pub fn try_list_directory_entries(
+    fs: &Fat32FileSystem,
+    cluster_id: u32,
+) -> Result<Vec<FileInfo>, FsError> {
+    let mut entries = fs.dir_iter(cluster_id);
+    let files = entries.by_ref().collect();
+    entries.check_chain()?;
+    Ok(files)
+}
 
-    const ENTRY_SIZE: usize = 32;
-    const ATTR_LFN: u8 = 0x0F;
-    const ATTR_DIRECTORY: u8 = 0x10;
+/// Liste les entrées d’un répertoire FAT32, y compris l’étiquette de volume.
+///
+/// Destinée aux outils bas niveau : l’étiquette est retournée comme un [`FileInfo`]
+/// dont le champ `is_volume_label` est activé.
+pub fn list_directory_entries_raw(fs: &Fat32FileSystem, cluster_id: u32) -> Vec<FileInfo> {
+    read_directory_entries(fs, cluster_id, true)
+}
 
-    for entry_chunk in cluster_data.chunks_exact(ENTRY_SIZE) {
-        let first_byte = entry_chunk[0];
-        let attributes = entry_chunk[11];
+/// Parcourt les entrées d’un répertoire pour [`list_directory_entries`] et
+/// [`list_directory_entries_raw`].
+fn read_directory_entries(
+    fs: &Fat32FileSystem,
+    cluster_id: u32,
+    include_volume_label: bool,
+) -> Vec<FileInfo> {
+    read_directory_slots(fs, cluster_id, include_volume_label)
+        .into_iter()
+        .map(|(file_info, _)| file_info)
+        .collect()
+}
 
-        // Fin des entrées
-        if first_byte == 0x00 {
-            break;
+/// Emplacements `(cluster, index)` des slots de 32 octets occupés par une entrée
+/// (fragments LFN puis entrée 8.3).
+type EntrySlots = Vec<(u32, usize)>;
+
+/// Parcourt les entrées d’un répertoire en conservant l’emplacement de leurs slots.
+fn read_directory_slots(
+    fs: &Fat32FileSystem,
+    cluster_id: u32,
+    include_volume_label: bool,
+) -> Vec<(FileInfo, EntrySlots)> {
+    DirSlots::new(fs, cluster_id, include_volume_label).collect()
+}
+
+/// Parcours paresseux des entrées d’un répertoire et de leurs slots, commun à
+/// [`read_directory_slots`] et [`DirIter`].
+///
+/// Le cluster suivant n’est lu (données et entrée FAT) qu’une fois le cluster courant
+/// épuisé ; comme pour [`Fat32FileSystem::cluster_chain`], le parcours s’arrête sur un
+/// cluster invalide ou déjà visité (`cyclic` est alors activé).
+struct DirSlots<'a> {
+    fs: &'a Fat32FileSystem,
+    next_cluster: Option<u32>,
+    visited: BTreeSet<u32>,
+    cyclic: bool,
+    fat_cache: Option<(u32, Vec<u8>)>,
+    cluster: u32,
+    data: Vec<u8>,
+    index: usize,
+    include_volume_label: bool,
+    lfn_fragments: LfnFragments,
+    expected_checksum: Option<u8>,
+    slots: EntrySlots,
+}
+
+impl<'a> DirSlots<'a> {
+    fn new(fs: &'a Fat32FileSystem, cluster_id: u32, include_volume_label: bool) -> Self {
+        DirSlots {
+            fs,
+            next_cluster: Some(cluster_id),
+            visited: BTreeSet::new(),
+            cyclic: false,
+            fat_cache: None,
+            cluster: cluster_id,
+            data: Vec::new(),
+            index: 0,
+            include_volume_label,
+            lfn_fragments: Vec::new(),
+            expected_checksum: None,
+            slots: Vec::new(),
         }
+    }
 
-        // Entrée supprimée
-        if first_byte == 0xE5 {
-            lfn_fragments.clear();
-            expected_checksum = None;
-            continue;
+    /// Charge le cluster suivant de la chaîne ; retourne `false` en fin de chaîne.
+    fn load_next_cluster(&mut self) -> bool {
+        let Some(cluster) = self
+            .next_cluster
+            .take()
+            .filter(|&cluster| self.fs.is_valid_cluster(cluster))
+        else {
+            return false;
+        };
+        if !self.visited.insert(cluster) {
+            self.cyclic = true;
+            return false;
         }
 
-        // Entrée LFN
-        if attributes == ATTR_LFN {
-            process_lfn_entry(entry_chunk, &mut lfn_fragments, &mut expected_checksum);
-        } else {
+        self.data = self.fs.read_cluster(cluster);
+        self.cluster = cluster;
+        self.index = 0;
+
+        let next = self.fs.read_fat_entry_cached(cluster, &mut self.fat_cache);
+        self.next_cluster = (!is_end_of_chain(next)).then_some(next);
+        true
+    }
+}
+
+impl Iterator for DirSlots<'_> {
+    type Item = (FileInfo, EntrySlots);
+
+    fn next(&mut self) -> Option<(FileInfo, EntrySlots)> {
+        const ENTRY_SIZE: usize = 32;
+
+        loop {
+            // Un répertoire dont le dernier cluster est plein n’a pas de marqueur 0x00 :
+            // le parcours s’arrête alors sur la fin de chaîne de la FAT.
+            if (self.index + 1) * ENTRY_SIZE > self.data.len() && !self.load_next_cluster() {
+                return None;
+            }
+
+            let idx = self.index;
+            self.index += 1;
+
+            let entry_chunk = &self.data[idx * ENTRY_SIZE..(idx + 1) * ENTRY_SIZE];
+            let first_byte = entry_chunk[0];
+            let attributes = entry_chunk[11];
+
+            // Fin des entrées
+            if first_byte == 0x00 {
+                self.next_cluster = None;
+                self.data.clear();
+                return None;
+            }
+
+            // Entrée supprimée
+            if first_byte == 0xE5 {
+                self.lfn_fragments.clear();
+                self.expected_checksum = None;
+                self.slots.clear();
+                continue;
+            }
+
+            // Entrée LFN
+            if EntryKind::classify(attributes) == EntryKind::LongName {
+                // Début d’une nouvelle séquence LFN
+                if first_byte & 0x40 != 0 {
+                    self.slots.clear();
+                }
+                process_lfn_entry(
+                    entry_chunk,
+                    &mut self.lfn_fragments,
+                    &mut self.expected_checksum,
+                );
+                self.slots.push((self.cluster, idx));
+                continue;
+            }
+
             // Entrée FAT classique
-            if let Some(file_info) = process_data_entry(
+            self.slots.push((self.cluster, idx));
+
+            let file_info = process_data_entry(
                 entry_chunk,
-                &mut lfn_fragments,
-                &mut expected_checksum,
-                ATTR_DIRECTORY,
-            ) {
-                results.push(file_info);
+                &mut self.lfn_fragments,
+                &mut self.expected_checksum,
+                self.fs.root_cluster,
+                self.include_volume_label,
+            );
+            let slots = core::mem::take(&mut self.slots);
+
+            self.lfn_fragments.clear();
+            self.expected_checksum = None;
+
+            if let Some(file_info) = file_info {
+                return Some((file_info, slots));
             }
+        }
+    }
+}
 
-            lfn_fragments.clear();
-            expected_checksum = None;
+/// Itérateur paresseux sur les entrées d’un répertoire, créé par
+/// [`Fat32FileSystem::dir_iter`].
+///
+/// Retourne les mêmes entrées que [`list_directory_entries`] (`.` et `..` compris), mais
+/// les clusters du répertoire ne sont lus qu’au fil de l’itération : les premières
+/// entrées sont disponibles sans parcourir toute la chaîne.
+pub struct DirIter<'a> {
+    slots: DirSlots<'a>,
+}
+
+impl DirIter<'_> {
+    /// Indique, une fois l’itération terminée, si elle s’est arrêtée sur un cluster déjà
+    /// visité plutôt que sur une fin de répertoire.
+    ///
+    /// # Errors
+    /// - [`FsError::CorruptChain`] si la chaîne du répertoire est cyclique
+    pub fn check_chain(&self) -> Result<(), FsError> {
+        match self.slots.cyclic {
+            true => Err(FsError::CorruptChain),
+            false => Ok(()),
         }
     }
+}
+
+impl Iterator for DirIter<'_> {
+    type Item = FileInfo;
+
+    fn next(&mut self) -> Option<FileInfo> {
+        self.slots.next().map(|(file_info, _)| file_info)
+    }
+}
+
+/// Liste les entrées supprimées (`0xE5`) d’un répertoire.
+///
+/// Le premier caractère du nom court étant écrasé par le marqueur de suppression,
+/// il est remplacé par `?`. La taille et le cluster de départ sont repris de l’entrée,
+/// les fragments LFN supprimés sont ignorés.
+pub fn list_deleted(fs: &Fat32FileSystem, cluster_id: u32) -> Vec<FileInfo> {
+    read_deleted_entries(fs, cluster_id)
+        .into_iter()
+        .map(|(file_info, _)| file_info)
+        .collect()
+}
+
+/// Parcourt les entrées supprimées d’un répertoire en conservant l’emplacement de leur slot 8.3.
+fn read_deleted_entries(fs: &Fat32FileSystem, cluster_id: u32) -> Vec<(FileInfo, (u32, usize))> {
+    fs.iter_dir_raw(cluster_id)
+        .take_while(|(entry, _, _)| entry[0] != 0x00)
+        .filter_map(|(entry, cluster, offset)| {
+            let kind = EntryKind::classify(entry[11]);
+            if entry[0] != 0xE5 || kind == EntryKind::LongName || kind == EntryKind::VolumeLabel {
+                return None;
+            }
+
+            let mut dir_entry = FatDir::new(&entry);
+            dir_entry.name[0] = b'?';
 
-    results
+            let file_info = FileInfo::new(
+                short_name_to_string(&dir_entry.name),
+                kind == EntryKind::Directory,
+                dir_entry.size,
+                dir_entry.first_cluster(),
+            );
+            Some((file_info, (cluster, offset / 32)))
+        })
+        .collect()
 }
 
 /// Traite une entrée Long File Name (LFN).
@@ -859,7 +3833,12 @@ fn process_lfn_entry(
 ///
 /// - Trie les fragments par numéro de séquence
 /// - Ignore les caractères de fin (`0x0000`, `0xFFFF`)
+/// - Ignore un BOM (`U+FEFF`) initial
 /// - Convertit UTF-16 → UTF-8
+/// - Supprime les espaces et points de fin ajoutés par certains formateurs
+///
+/// Retourne `None` si aucun fragment n’est présent ou si le nom n’est pas de l’UTF-16
+/// valide (surrogate isolé) : l’appelant utilise alors le nom court
 fn assemble_lfn(lfn_fragments: &LfnFragments) -> Option<String> {
     if lfn_fragments.is_empty() {
         return None;
@@ -881,34 +3860,64 @@ fn assemble_lfn(lfn_fragments: &LfnFragments) -> Option<String> {
         }
     }
 
-    String::from_utf16(&utf16_chars).ok()
+    if utf16_chars.first() == Some(&0xFEFF) {
+        utf16_chars.remove(0);
+    }
+
+    let name = String::from_utf16(&utf16_chars).ok()?;
+
+    // Un nom composé uniquement de points (`.`) est conservé tel quel
+    let trimmed = name.trim_end_matches([' ', '.']);
+    if trimmed.is_empty() {
+        Some(name)
+    } else {
+        Some(trimmed.to_string())
+    }
 }
 
 /// Traite une entrée FAT classique et construit un [`FileInfo`]
 ///
 /// - Vérifie le checksum LFN
-/// - Détermine le type (fichier ou répertoire)
+/// - Détermine le type (étiquette, répertoire ou fichier, voir [`EntryKind::classify`])
 /// - Calcule le cluster de départ
 fn process_data_entry(
     entry_chunk: &[u8],
     lfn_fragments: &mut LfnFragments,
     expected_checksum: &mut Option<u8>,
-    attr_directory_mask: u8,
+    root_cluster: u32,
+    include_volume_label: bool,
 ) -> Option<FileInfo> {
     let dir_entry = FatDir::new(entry_chunk);
+    let kind = EntryKind::classify(dir_entry.attr);
 
     // Volume label
-    if dir_entry.attr & 0x08 != 0 {
-        return None;
-    }
+    if kind == EntryKind::VolumeLabel {
+        if !include_volume_label {
+            return None;
+        }
 
-    let start_cluster =
-        ((dir_entry.first_cluster_high as u32) << 16) | (dir_entry.first_cluster_low as u32);
+        let label = core::str::from_utf8(&dir_entry.name)
+            .unwrap_or("")
+            .trim_end()
+            .to_string();
+
+        return Some(FileInfo {
+            is_volume_label: true,
+            attributes: dir_entry.attr,
+            ..FileInfo::new(label, false, 0, 0)
+        });
+    }
 
-    let is_directory = (dir_entry.attr & attr_directory_mask) != 0;
+    let is_directory = kind == EntryKind::Directory;
+    let start_cluster = if is_directory {
+        entry_start_cluster(&dir_entry, root_cluster)
+    } else {
+        dir_entry.first_cluster()
+    };
     let size = dir_entry.size;
 
     let mut name_to_use: Option<String> = None;
+    let mut lfn_fallback = false;
 
     // Tentative de reconstruction LFN
     if !lfn_fragments.is_empty() {
@@ -916,6 +3925,7 @@ fn process_data_entry(
         if let Some(expected) = expected_checksum {
             if *expected == computed {
                 name_to_use = assemble_lfn(&lfn_fragments);
+                lfn_fallback = name_to_use.is_none();
             } else {
                 lfn_fragments.clear();
             }
@@ -929,12 +3939,19 @@ fn process_data_entry(
         name_to_use = Some(short_name_to_string(&dir_entry.name));
     }
 
-    Some(FileInfo::new(
-        name_to_use.unwrap_or_default(),
-        is_directory,
-        size,
-        start_cluster,
-    ))
+    Some(FileInfo {
+        created: FatTimestamp::from_fat(dir_entry.create_date, dir_entry.create_time),
+        modified: FatTimestamp::from_fat(dir_entry.write_date, dir_entry.write_time),
+        lfn_fallback,
+        is_dot_entry: &dir_entry.name == b".          " || &dir_entry.name == b"..         ",
+        attributes: dir_entry.attr,
+        ..FileInfo::new(
+            name_to_use.unwrap_or_default(),
+            is_directory,
+            size,
+            start_cluster,
+        )
+    })
 }
 
 /// Retourne uniquement les noms des fichiers
@@ -943,3 +3960,175 @@ fn process_data_entry(
 pub fn list_files_names<'a>(files: &'a [FileInfo]) -> Vec<&'a str> {
     files.iter().map(|f| f.name.as_str()).collect()
 }
+
+#[test_case]
+fn test_assemble_lfn_trims_padding() {
+    let fragment = |name: &str| -> Vec<u16> {
+        let mut chars: Vec<u16> = name.encode_utf16().collect();
+        chars.push(0x0000);
+        chars
+    };
+
+    let padded: LfnFragments = vec![(1, fragment("report.txt "))];
+    assert_eq!(assemble_lfn(&padded), Some("report.txt".to_string()));
+
+    let interior: LfnFragments = vec![(1, fragment("my file.tar.gz. "))];
+    assert_eq!(assemble_lfn(&interior), Some("my file.tar.gz".to_string()));
+
+    let bom: LfnFragments = vec![(1, fragment("\u{FEFF}notes.txt"))];
+    assert_eq!(assemble_lfn(&bom), Some("notes.txt".to_string()));
+
+    let surrogate: LfnFragments = vec![(1, vec![0x0061, 0xD800, 0x0062, 0x0000])];
+    assert_eq!(assemble_lfn(&surrogate), None);
+
+    let dot: LfnFragments = vec![(1, fragment("."))];
+    assert_eq!(assemble_lfn(&dot), Some(".".to_string()));
+}
+
+#[test_case]
+fn test_to_short_name_round_trip() {
+    let short = to_short_name("file.txt").unwrap();
+    assert_eq!(&short, b"FILE    TXT");
+    assert_eq!(short_name_to_string(&short), "FILE.TXT");
+
+    assert_eq!(
+        short_name_to_string(&to_short_name("TEST_DIR").unwrap()),
+        "TEST_DIR"
+    );
+    assert_eq!(to_short_name("a.b.c"), None);
+
+    assert_eq!(to_short_name("toolongname.txt"), None);
+    assert_eq!(to_short_name("file.text"), None);
+    assert_eq!(to_short_name(".hidden"), None);
+    assert_eq!(to_short_name("bad*name"), None);
+    assert_eq!(to_short_name("with space"), None);
+}
+
+#[test_case]
+fn test_short_name_cow_borrows_simple_names() {
+    assert!(matches!(
+        short_name_cow(b"TEST_DIR   "),
+        Cow::Borrowed("TEST_DIR")
+    ));
+    assert!(matches!(
+        short_name_cow(b"SUB        "),
+        Cow::Borrowed("SUB")
+    ));
+
+    let with_ext = short_name_cow(b"FILE    TXT");
+    assert!(matches!(with_ext, Cow::Owned(_)));
+    assert_eq!(with_ext, "FILE.TXT");
+}
+
+#[test_case]
+fn test_short_name_decodes_cp437() {
+    // 0xE9 vaut `Θ` et 0x82 `é` en page de code 437 : le reste du nom est conservé
+    assert_eq!(short_name_to_string(b"CAF\xE9    TXT"), "CAFΘ.TXT");
+    assert_eq!(short_name_to_string(b"CAF\x82       "), "CAFé");
+    assert_eq!(short_name_to_string(b"NOTE    \x82TX"), "NOTE.éTX");
+
+    // Un premier octet 0x05 remplace 0xE5 (marqueur de suppression)
+    assert_eq!(short_name_to_string(b"\x05TAT       "), "σTAT");
+    assert_eq!(cp437_char(0xFF), '\u{A0}');
+}
+
+#[test_case]
+fn test_crc32_table() {
+    assert_eq!(CRC32_TABLE[1], 0x77073096);
+    assert_eq!(CRC32_TABLE[255], 0x2D02EF8D);
+
+    let fs = Fat32FileSystem::new_from_parts(Box::from(&b"123456789"[..]), 512, 1, 1, 2, 2);
+    assert_eq!(fs.checksum(), 0xCBF43926);
+}
+
+#[test_case]
+fn test_write_entry_at_round_trip() {
+    let mut disk = vec![0u8; 6 * 512];
+    disk[512 + 8..512 + 12].copy_from_slice(&FAT_EOC_MARK.to_le_bytes());
+    let mut fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+
+    let mut entry = [0u8; 32];
+    entry[0..11].copy_from_slice(b"KEEP    TXT");
+    entry[11] = 0x20;
+    fs.write_entry_at(2, 0, &entry).unwrap();
+
+    entry[0..11].copy_from_slice(b"CRAFT   TXT");
+    entry[26..28].copy_from_slice(&3u16.to_le_bytes());
+    entry[28..32].copy_from_slice(&42u32.to_le_bytes());
+    fs.write_entry_at(2, 32, &entry).unwrap();
+
+    let location = fs.locate_entry("/CRAFT.TXT", None).unwrap();
+    assert_eq!(
+        location,
+        EntryLocation {
+            cluster: 2,
+            offset_in_cluster: 32
+        }
+    );
+    assert_eq!(
+        fs.read_entry_at(location.cluster, location.offset_in_cluster),
+        Ok(entry)
+    );
+
+    let file = fs.resolve("/CRAFT.TXT", None).unwrap().file;
+    assert_eq!((file.start_cluster, file.size), (3, 42));
+
+    assert_eq!(fs.write_entry_at(2, 16, &entry), Err(FsError::OutOfBounds));
+    assert_eq!(fs.write_entry_at(2, 512, &entry), Err(FsError::OutOfBounds));
+}
+
+#[test_case]
+fn test_file_info_to_entry_preserves_timestamps() {
+    let mut original = [0u8; 32];
+    original[0..11].copy_from_slice(b"FILE    TXT");
+    original[11] = ATTR_ARCHIVE | ATTR_READ_ONLY;
+    original[12..20].copy_from_slice(&[0x18, 0x7D, 0x2A, 0x5C, 0x21, 0x5B, 0x21, 0x5B]);
+    original[22..26].copy_from_slice(&[0x2B, 0x5C, 0x21, 0x5B]);
+    original[26..28].copy_from_slice(&3u16.to_le_bytes());
+    original[28..32].copy_from_slice(&10u32.to_le_bytes());
+
+    let info = FileInfo {
+        size: 1234,
+        ..FileInfo::new("FILE.TXT".to_string(), false, 10, 3)
+    };
+    let rewritten = file_info_to_entry(&info, &original);
+
+    assert_eq!(FatDir::new(&rewritten).size, 1234);
+    assert_eq!(rewritten[..28], original[..28]);
+}
+
+#[test_case]
+fn test_overwrite_preserves_timestamps() {
+    let mut disk = vec![0u8; 6 * 512];
+    disk[512 + 8..512 + 12].copy_from_slice(&FAT_EOC_MARK.to_le_bytes());
+    disk[512 + 12..512 + 16].copy_from_slice(&FAT_EOC_MARK.to_le_bytes());
+    let mut fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+
+    let mut original = [0u8; 32];
+    original[0..11].copy_from_slice(b"FILE    TXT");
+    original[11] = ATTR_ARCHIVE | ATTR_HIDDEN;
+    original[12..20].copy_from_slice(&[0x18, 0x7D, 0x2A, 0x5C, 0x21, 0x5B, 0x21, 0x5B]);
+    original[26..28].copy_from_slice(&3u16.to_le_bytes());
+    original[28..32].copy_from_slice(&10u32.to_le_bytes());
+    fs.write_entry_at(2, 0, &original).unwrap();
+
+    fs.write_file("/FILE.TXT", None, b"rewritten").unwrap();
+
+    let entry = fs.read_entry_at(2, 0).unwrap();
+    assert_eq!(FatDir::new(&entry).size, 9);
+    assert_eq!(entry[..12], original[..12]);
+    assert_eq!(entry[13..18], original[13..18]);
+    assert_eq!(entry[20..22], original[20..22]);
+    assert_eq!(entry[26..28], original[26..28]);
+}
+
+#[test_case]
+fn test_is_end_of_chain_boundaries() {
+    assert!(!is_end_of_chain(FAT_FREE));
+    assert!(!is_end_of_chain(2));
+    assert!(!is_end_of_chain(FAT_BAD));
+    assert!(is_end_of_chain(FAT_EOC));
+    assert!(is_end_of_chain(FAT_EOC_MARK));
+    assert!(is_end_of_chain(0xFFFFFFF8));
+    assert!(!is_end_of_chain(0xF0000002));
+}
@@ -0,0 +1,207 @@
+//! Vérification en lecture seule de l’intégrité d’un volume FAT32
+//!
+//! [`Fat32FileSystem::check`] exécute des contrôles légers (signature, géométrie, copies
+//! de la FAT, compteur de clusters libres, chaînes perdues ou partagées, entrées
+//! incohérentes) et retourne la liste des anomalies trouvées sans modifier le disque,
+//! à la manière d’un `fsck --readonly`.
+
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{FAT_BAD, FAT_FREE, Fat32FileSystem};
+
+/// Sous-vérifications exécutées par [`Fat32FileSystem::check_with`].
+///
+/// Chaque contrôle peut être désactivé pour limiter le coût de la vérification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckOptions {
+    /// Vérifie la signature `0x55AA` du secteur de boot.
+    pub signature: bool,
+    /// Vérifie que la longueur de l’image est un multiple de la taille de secteur et
+    /// qu’elle couvre tout le volume décrit par le secteur de boot.
+    pub geometry: bool,
+    /// Compare les copies miroirs de la FAT à la FAT principale.
+    pub fat_mirrors: bool,
+    /// Compare le compteur de clusters libres du FSInfo à la FAT.
+    pub free_count: bool,
+    /// Recherche les chaînes perdues et les clusters partagés.
+    pub chains: bool,
+    /// Recherche les entrées incohérentes (fichier non vide sans cluster de départ).
+    pub entries: bool,
+}
+
+impl Default for CheckOptions {
+    fn default() -> Self {
+        CheckOptions {
+            signature: true,
+            geometry: true,
+            fat_mirrors: true,
+            free_count: true,
+            chains: true,
+            entries: true,
+        }
+    }
+}
+
+/// Anomalie relevée lors d’une vérification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FsWarning {
+    /// Le secteur de boot ne se termine pas par la signature `0x55AA`.
+    BadSignature,
+
+    /// La longueur de l’image n’est pas un multiple de la taille de secteur : le dernier
+    /// secteur, incomplet, est inaccessible.
+    UnalignedDisk {
+        /// Octets en fin d’image hors de tout secteur complet.
+        trailing: usize,
+    },
+
+    /// L’image est plus courte que le volume décrit par le secteur de boot
+    /// (`BPB_TotSec32`) : les derniers secteurs sont absents.
+    TruncatedImage {
+        /// Octets manquants en fin d’image.
+        missing: u64,
+    },
+
+    /// Une copie de la FAT diffère de la FAT principale.
+    FatMirrorMismatch {
+        /// Index de la copie divergente.
+        copy: u32,
+        /// Premier cluster dont l’entrée diffère.
+        cluster: u32,
+    },
+
+    /// Le compteur du FSInfo ne correspond pas au nombre de clusters libres de la FAT.
+    FreeCountMismatch {
+        /// Valeur enregistrée dans le FSInfo.
+        fsinfo: u32,
+        /// Valeur recalculée depuis la FAT.
+        actual: u32,
+    },
+
+    /// Clusters marqués utilisés mais attachés à aucune entrée.
+    LostClusters(Vec<u32>),
+
+    /// Cluster appartenant à plusieurs entrées.
+    CrossLinked {
+        /// Cluster partagé.
+        cluster: u32,
+        /// Chemins des entrées qui le référencent.
+        paths: Vec<String>,
+    },
+
+    /// Fichier de taille non nulle dont le cluster de départ vaut 0 : ses données sont
+    /// introuvables (typiquement un `first_cluster_high` laissé à 0 par un outil FAT16).
+    SizeWithoutCluster {
+        /// Chemin de l’entrée.
+        path: String,
+        /// Taille déclarée par l’entrée.
+        size: u32,
+    },
+}
+
+/// Résultat d’une vérification du volume.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FsReport {
+    /// Anomalies relevées, dans l’ordre des vérifications.
+    pub warnings: Vec<FsWarning>,
+}
+
+impl FsReport {
+    /// Indique si aucune anomalie n’a été relevée.
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+impl Fat32FileSystem {
+    /// Vérifie l’intégrité du volume avec toutes les sous-vérifications.
+    pub fn check(&self) -> FsReport {
+        self.check_with(&CheckOptions::default())
+    }
+
+    /// Vérifie l’intégrité du volume avec les sous-vérifications choisies.
+    ///
+    /// Le disque n’est jamais modifié.
+    pub fn check_with(&self, options: &CheckOptions) -> FsReport {
+        let mut warnings = Vec::new();
+
+        if options.signature && self.disk.get(510..512) != Some(&[0x55, 0xAA][..]) {
+            warnings.push(FsWarning::BadSignature);
+        }
+
+        let trailing = self.trailing_bytes();
+        if options.geometry && trailing != 0 {
+            warnings.push(FsWarning::UnalignedDisk { trailing });
+        }
+
+        let missing = self.missing_bytes();
+        if options.geometry && missing != 0 {
+            warnings.push(FsWarning::TruncatedImage { missing });
+        }
+
+        if options.fat_mirrors {
+            for (copy, cluster) in self.verify_fats() {
+                warnings.push(FsWarning::FatMirrorMismatch { copy, cluster });
+            }
+        }
+
+        if let Some(fsinfo) = self.fsinfo_free_count().filter(|_| options.free_count) {
+            let actual = self.recompute_free_count();
+            if fsinfo != actual {
+                warnings.push(FsWarning::FreeCountMismatch { fsinfo, actual });
+            }
+        }
+
+        if options.chains {
+            let lost = self.lost_clusters();
+            if !lost.is_empty() {
+                warnings.push(FsWarning::LostClusters(lost));
+            }
+
+            for (cluster, paths) in self.find_cross_links() {
+                warnings.push(FsWarning::CrossLinked { cluster, paths });
+            }
+        }
+
+        if options.entries {
+            self.walk(
+                |_| false,
+                |path, file| {
+                    if !file.is_directory && file.start_cluster == 0 && file.size != 0 {
+                        warnings.push(FsWarning::SizeWithoutCluster {
+                            path: path.into(),
+                            size: file.size,
+                        });
+                    }
+                },
+            );
+        }
+
+        FsReport { warnings }
+    }
+
+    /// Retourne les clusters utilisés dans la FAT mais absents de toute chaîne atteignable.
+    fn lost_clusters(&self) -> Vec<u32> {
+        let mut reachable: BTreeSet<u32> =
+            self.cluster_chain(self.root_cluster).into_iter().collect();
+
+        self.walk(
+            |_| false,
+            |_, file| {
+                reachable.extend(self.cluster_chain(file.start_cluster));
+            },
+        );
+
+        self.fat_entries()
+            .iter()
+            .enumerate()
+            .skip(2)
+            .filter(|&(cluster, &entry)| {
+                entry != FAT_FREE && entry != FAT_BAD && !reachable.contains(&(cluster as u32))
+            })
+            .map(|(cluster, _)| cluster as u32)
+            .collect()
+    }
+}
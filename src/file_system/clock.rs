@@ -0,0 +1,80 @@
+//! Source de temps utilisée pour horodater les entrées FAT32
+//!
+//! Le noyau n’expose pas d’horloge temps réel à la couche FAT : le système de fichiers
+//! s’appuie sur un [`FatClock`] fourni par l’appelant, ou à défaut sur l’époque FAT
+//! (1er janvier 1980).
+
+use core::fmt;
+
+/// Date et heure au format FAT (résolution de 2 secondes sur disque).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FatTimestamp {
+    /// Année (1980 à 2107).
+    pub year: u16,
+    /// Mois (1 à 12).
+    pub month: u8,
+    /// Jour du mois (1 à 31).
+    pub day: u8,
+    /// Heure (0 à 23).
+    pub hour: u8,
+    /// Minute (0 à 59).
+    pub minute: u8,
+    /// Seconde (0 à 59).
+    pub second: u8,
+}
+
+impl FatTimestamp {
+    /// Époque FAT : 1er janvier 1980 à minuit.
+    pub const EPOCH: FatTimestamp = FatTimestamp::new(1980, 1, 1, 0, 0, 0);
+
+    /// Construit un nouvel horodatage.
+    pub const fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+        FatTimestamp {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        }
+    }
+
+    /// Décode un horodatage à partir des champs date et heure d’une entrée FAT.
+    pub fn from_fat(date: u16, time: u16) -> Self {
+        FatTimestamp {
+            year: 1980 + (date >> 9),
+            month: ((date >> 5) & 0x0F) as u8,
+            day: (date & 0x1F) as u8,
+            hour: (time >> 11) as u8,
+            minute: ((time >> 5) & 0x3F) as u8,
+            second: ((time & 0x1F) * 2) as u8,
+        }
+    }
+
+    /// Encode la date au format FAT (`année-1980 << 9 | mois << 5 | jour`).
+    pub fn to_fat_date(&self) -> u16 {
+        ((self.year.saturating_sub(1980)) << 9) | ((self.month as u16) << 5) | self.day as u16
+    }
+
+    /// Encode l’heure au format FAT (`heure << 11 | minute << 5 | seconde / 2`).
+    pub fn to_fat_time(&self) -> u16 {
+        ((self.hour as u16) << 11) | ((self.minute as u16) << 5) | (self.second as u16 / 2)
+    }
+}
+
+impl fmt::Display for FatTimestamp {
+    /// Affiche l’horodatage au format `AAAA-MM-JJ hh:mm:ss`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// Source de temps pour l’horodatage des créations et modifications.
+pub trait FatClock: fmt::Debug {
+    /// Retourne la date et l’heure courantes.
+    fn now(&self) -> FatTimestamp;
+}
@@ -0,0 +1,355 @@
+//! Copie et déplacement d’entrées (`cp`, `cp -r`, `mv`)
+//!
+//! Les copies écrivent les données cluster par cluster et signalent leur avancement via
+//! un rappel [`Progress`] optionnel, afin qu’une interface puisse afficher la progression
+//! d’une opération longue sur un périphérique lent.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{
+    DirOffsets, EntryLocation, Fat32FileSystem, FileInfo, FsError, file_info_to_entry,
+    read_directory_slots,
+};
+
+/// Rappel d’avancement, appelé avec `(octets écrits, octets à écrire)`.
+pub type Progress<'a> = &'a mut dyn FnMut(u64, u64);
+
+/// Élément d’une arborescence à copier : composants du chemin relatif au répertoire copié,
+/// entrée 8.3 source et contenu (`None` pour un répertoire).
+type TreeItem = (Vec<String>, [u8; 32], Option<Vec<u8>>);
+
+/// Destination d’une nouvelle entrée : répertoire parent, nom court et fragments LFN.
+type EntryTarget = (u32, [u8; 11], Vec<[u8; 32]>);
+
+impl Fat32FileSystem {
+    /// Copie un fichier (`cp`).
+    ///
+    /// Si `dst` désigne un répertoire existant, la copie y est créée sous le nom du
+    /// source ; sinon `dst` est le chemin du nouveau fichier, dont le parent doit exister.
+    /// Un nom long est conservé (fragments LFN et alias 8.3 générés).
+    /// La copie reçoit des horodatages neufs, sauf si `preserve` est activé (`cp -p`) :
+    /// les dates de création, d’accès et de modification de la source sont alors recopiées.
+    /// `progress` est appelé après chaque cluster écrit.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`]
+    /// - [`FsError::NotAFile`] si la source est un répertoire
+    /// - [`FsError::AlreadyExists`] si la destination existe déjà
+    /// - [`FsError::InvalidName`] si le nom de destination est invalide
+    /// - [`FsError::DiskFull`] si l’espace est saturé : la copie incomplète est supprimée
+    ///   et ses clusters libérés
+    pub fn copy_file(
+        &mut self,
+        src: &str,
+        dst: &str,
+        current_cluster: Option<u32>,
+        preserve: bool,
+        mut progress: Option<Progress<'_>>,
+    ) -> Result<(), FsError> {
+        self.ensure_writable()?;
+
+        let file = self.resolve(src, current_cluster)?.file;
+        if file.is_directory {
+            return Err(FsError::NotAFile);
+        }
+
+        let source = self.locate_entry(src, current_cluster)?;
+        let source = self.read_entry_at(source.cluster, source.offset_in_cluster)?;
+
+        let (parent, short_name, lfn) = self.copy_target(&file, dst, current_cluster)?;
+        let data = self.read_file_info(&file)?;
+
+        let location = self.create_file_in(parent, short_name, &lfn)?;
+        let written =
+            self.write_with_progress(&location, &data, &mut 0, data.len() as u64, &mut progress);
+        if let Err(error) = written {
+            self.discard_entry(parent, &location)?;
+            return Err(error);
+        }
+
+        if preserve {
+            self.copy_timestamps(&source, &location)?;
+        }
+
+        Ok(())
+    }
+
+    /// Copie récursivement un répertoire (`cp -r`).
+    ///
+    /// La destination suit les règles de [`Self::copy_file`]. L’arborescence source est
+    /// lue entièrement avant la première écriture, ce qui rend sûre une copie dans un de
+    /// ses propres sous-répertoires. `preserve` s’applique à chaque entrée copiée, y compris
+    /// aux répertoires. `progress` reçoit le total des octets de tous les fichiers copiés.
+    /// Si l’espace vient à manquer, le fichier en cours est supprimé comme avec
+    /// [`Self::copy_file`] ; les entrées déjà copiées restent en place.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::copy_file`]
+    /// - [`FsError::NotADirectory`] si la source est un fichier
+    pub fn copy_tree(
+        &mut self,
+        src: &str,
+        dst: &str,
+        current_cluster: Option<u32>,
+        preserve: bool,
+        mut progress: Option<Progress<'_>>,
+    ) -> Result<(), FsError> {
+        self.ensure_writable()?;
+
+        let dir = self.resolve(src, current_cluster)?.file;
+        if !dir.is_directory {
+            return Err(FsError::NotADirectory);
+        }
+
+        let (parent, short_name, lfn) = self.copy_target(&dir, dst, current_cluster)?;
+
+        let mut items = Vec::new();
+        let mut visited = BTreeSet::new();
+        self.collect_tree(
+            self.dir_cluster(&dir),
+            &mut Vec::new(),
+            &mut items,
+            &mut visited,
+        )?;
+
+        let total = items
+            .iter()
+            .filter_map(|(_, _, data)| data.as_ref())
+            .map(|data| data.len() as u64)
+            .sum();
+        let mut done = 0;
+
+        let mut clusters = BTreeMap::new();
+        clusters.insert(Vec::new(), self.mkdir_in(parent, short_name, &lfn)?);
+
+        // La racine n’a pas d’entrée : ses horodatages ne peuvent pas être recopiés
+        let root_source = self
+            .locate_entry(src, current_cluster)
+            .ok()
+            .filter(|_| preserve);
+        if let Some(source) = root_source {
+            let source = self.read_entry_at(source.cluster, source.offset_in_cluster)?;
+            let location = self.short_entry_location(parent, short_name)?;
+            self.copy_timestamps(&source, &location)?;
+        }
+
+        for (names, source, content) in items {
+            let (name, dirs) = names.split_last().ok_or(FsError::InvalidName)?;
+            let parent = clusters[dirs];
+            let (short_name, lfn) = self.entry_names(parent, name, None)?;
+
+            let location = match content {
+                None => {
+                    let cluster = self.mkdir_in(parent, short_name, &lfn)?;
+                    clusters.insert(names, cluster);
+                    self.short_entry_location(parent, short_name)?
+                }
+                Some(data) => {
+                    let location = self.create_file_in(parent, short_name, &lfn)?;
+                    let written =
+                        self.write_with_progress(&location, &data, &mut done, total, &mut progress);
+                    if let Err(error) = written {
+                        self.discard_entry(parent, &location)?;
+                        return Err(error);
+                    }
+                    location
+                }
+            };
+
+            if preserve {
+                self.copy_timestamps(&source, &location)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Déplace ou renomme une entrée (`mv`).
+    ///
+    /// La destination suit les règles de [`Self::copy_file`]. Les slots de l’entrée sont
+    /// réécrits dans le répertoire de destination (l’entrée 8.3 conserve ses horodatages et
+    /// ses attributs) : la chaîne de clusters est conservée et aucune donnée n’est copiée,
+    /// `progress` est donc appelé une seule fois avec la taille de l’entrée. Le `..` d’un
+    /// répertoire déplacé est mis à jour.
+    ///
+    /// # Errors
+    /// - Erreurs de [`Self::resolve`]
+    /// - [`FsError::AlreadyExists`] si la destination existe déjà
+    /// - [`FsError::InvalidName`] si le nom de destination est invalide, ou si un répertoire
+    ///   est déplacé dans son propre sous-arbre
+    /// - [`FsError::DiskFull`] si le répertoire de destination ne peut pas être agrandi
+    pub fn move_entry(
+        &mut self,
+        src: &str,
+        dst: &str,
+        current_cluster: Option<u32>,
+        progress: Option<Progress<'_>>,
+    ) -> Result<(), FsError> {
+        self.ensure_writable()?;
+
+        let resolved = self.resolve(src, current_cluster)?;
+        let file = resolved.file;
+        if file.is_dot_entry {
+            return Err(FsError::NotFound);
+        }
+
+        let (parent, short_name, lfn) = self.copy_target(&file, dst, current_cluster)?;
+
+        if file.is_directory {
+            let mut ancestors = BTreeSet::new();
+            let mut ancestor = parent;
+
+            while ancestor != self.root_cluster && ancestors.insert(ancestor) {
+                if ancestor == file.start_cluster {
+                    return Err(FsError::InvalidName);
+                }
+                ancestor = match self.find_parent_cluster(ancestor) {
+                    Some(0) | None => self.root_cluster,
+                    Some(cluster) => cluster,
+                };
+            }
+        }
+
+        let slots = read_directory_slots(self, resolved.parent_cluster, false)
+            .into_iter()
+            .find(|(f, _)| *f == file)
+            .map(|(_, slots)| slots)
+            .ok_or(FsError::NotFound)?;
+        let &(sfn_cluster, sfn_idx) = slots.last().ok_or(FsError::NotFound)?;
+
+        let mut entry = file_info_to_entry(&file, &self.read_entry_at(sfn_cluster, sfn_idx * 32)?);
+        entry[0..11].copy_from_slice(&short_name);
+
+        self.write_entry_run(parent, &lfn, &entry)?;
+
+        self.mark_slots_deleted(&slots)?;
+
+        if file.is_directory && file.start_cluster >= 2 {
+            self.fix_dotdot(file.start_cluster, parent)?;
+        }
+
+        if let Some(progress) = progress {
+            progress(file.size as u64, file.size as u64);
+        }
+
+        Ok(())
+    }
+
+    /// Retourne la destination d’une copie ou d’un déplacement de `src` vers `dst`.
+    fn copy_target(
+        &self,
+        src: &FileInfo,
+        dst: &str,
+        current_cluster: Option<u32>,
+    ) -> Result<EntryTarget, FsError> {
+        let (parent, name) = match self.resolve(dst, current_cluster) {
+            // La racine n’a pas d’entrée : `resolve("/")` ne la trouve pas
+            _ if dst.starts_with('/') && dst.trim_matches('/').is_empty() => {
+                (self.root_cluster, &*src.name)
+            }
+            Ok(target) if target.file.is_directory => (self.dir_cluster(&target.file), &*src.name),
+            Ok(_) => return Err(FsError::AlreadyExists),
+            Err(FsError::NotFound) => self.split_parent(dst, current_cluster)?,
+            Err(error) => return Err(error),
+        };
+
+        let (short_name, lfn) = self.entry_names(parent, name, None)?;
+        Ok((parent, short_name, lfn))
+    }
+
+    /// Collecte en préordre le contenu du répertoire `cluster` (un répertoire précède
+    /// toujours ses enfants).
+    fn collect_tree(
+        &self,
+        cluster: u32,
+        prefix: &mut Vec<String>,
+        items: &mut Vec<TreeItem>,
+        visited: &mut BTreeSet<u32>,
+    ) -> Result<(), FsError> {
+        if !visited.insert(cluster) {
+            return Ok(());
+        }
+
+        for (file, slots) in read_directory_slots(self, cluster, false) {
+            if file.is_dot_entry {
+                continue;
+            }
+
+            let &(entry_cluster, idx) = slots.last().ok_or(FsError::NotFound)?;
+            let source = self.read_entry_at(entry_cluster, idx * 32)?;
+            prefix.push(file.name.clone());
+
+            if file.is_directory {
+                items.push((prefix.clone(), source, None));
+                self.collect_tree(file.start_cluster, prefix, items, visited)?;
+            } else {
+                items.push((prefix.clone(), source, Some(self.read_file_info(&file)?)));
+            }
+
+            prefix.pop();
+        }
+
+        Ok(())
+    }
+
+    /// Retourne l’emplacement de l’entrée 8.3 `short_name` du répertoire `dir_cluster`.
+    fn short_entry_location(
+        &self,
+        dir_cluster: u32,
+        short_name: [u8; 11],
+    ) -> Result<EntryLocation, FsError> {
+        for (_, slots) in read_directory_slots(self, dir_cluster, false) {
+            let &(cluster, idx) = slots.last().ok_or(FsError::NotFound)?;
+            if self.read_entry_at(cluster, idx * 32)?[0..11] == short_name {
+                return Ok(EntryLocation {
+                    cluster,
+                    offset_in_cluster: idx * 32,
+                });
+            }
+        }
+
+        Err(FsError::NotFound)
+    }
+
+    /// Recopie les horodatages bruts (création, accès, modification) de `source` dans
+    /// l’entrée de `location`.
+    fn copy_timestamps(
+        &mut self,
+        source: &[u8; 32],
+        location: &EntryLocation,
+    ) -> Result<(), FsError> {
+        let mut entry = self.read_entry_at(location.cluster, location.offset_in_cluster)?;
+
+        let created = DirOffsets::CrtTimeTenth as usize..DirOffsets::FstClusHI as usize;
+        let modified = DirOffsets::WrtTime as usize..DirOffsets::FstClusLO as usize;
+        entry[created.clone()].copy_from_slice(&source[created]);
+        entry[modified.clone()].copy_from_slice(&source[modified]);
+
+        self.write_entry_at(location.cluster, location.offset_in_cluster, &entry)
+    }
+
+    /// Ajoute `data` au fichier de `location` cluster par cluster, en signalant l’avancement.
+    fn write_with_progress(
+        &mut self,
+        location: &EntryLocation,
+        data: &[u8],
+        done: &mut u64,
+        total: u64,
+        progress: &mut Option<Progress<'_>>,
+    ) -> Result<(), FsError> {
+        let cluster_bytes = (self.bytes_per_sector * self.sectors_per_cluster) as usize;
+
+        for chunk in data.chunks(cluster_bytes) {
+            self.append_at(location, chunk)?;
+            *done += chunk.len() as u64;
+
+            if let Some(progress) = progress {
+                progress(*done, total);
+            }
+        }
+
+        Ok(())
+    }
+}
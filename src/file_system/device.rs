@@ -0,0 +1,18 @@
+//! Périphérique de blocs sous-jacent au volume
+//!
+//! L’image FAT32 est montée en mémoire, mais elle provient d’un périphérique de blocs.
+//! Celui-ci peut fournir un [`ReadHook`] prévenu de chaque lecture de secteurs et capable
+//! de la refuser (bloc défectueux), sans que le système de fichiers en dépende.
+
+use core::fmt;
+
+use super::FsError;
+
+/// Observateur des lectures de secteurs du volume, fourni par le périphérique de blocs.
+pub trait ReadHook: fmt::Debug {
+    /// Appelé avant la lecture de `count` secteurs consécutifs à partir de `start`.
+    ///
+    /// # Errors
+    /// Une erreur (par exemple [`FsError::BadSector`]) fait échouer la lecture.
+    fn before_read(&self, start: u32, count: u32) -> Result<(), FsError>;
+}
@@ -0,0 +1,120 @@
+//! Erreurs retournées par le système de fichiers FAT32
+
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// Erreur d’une opération sur le système de fichiers FAT32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsError {
+    /// Le chemin ne correspond à aucune entrée.
+    NotFound,
+
+    /// L’entrée est un répertoire alors qu’un fichier est attendu.
+    NotAFile,
+
+    /// L’entrée est un fichier alors qu’un répertoire est attendu.
+    NotADirectory,
+
+    /// Le contenu du fichier n’est pas de l’UTF-8 valide.
+    InvalidUtf8,
+
+    /// La lecture dépasse la fin de l’image disque.
+    OutOfBounds,
+
+    /// Aucun cluster libre n’est disponible.
+    DiskFull,
+
+    /// Le nom ne peut pas être représenté en 8.3.
+    InvalidName,
+
+    /// Un cluster de la chaîne a été réalloué à une autre entrée.
+    ClusterReallocated,
+
+    /// Une entrée de répertoire est incohérente avec la structure du volume.
+    CorruptEntry,
+
+    /// La géométrie du volume (taille de secteur, de cluster) est invalide.
+    BadGeometry,
+
+    /// Le secteur de boot de secours diffère du secteur de boot principal.
+    BootMismatch,
+
+    /// Le fichier dépasse la taille de lecture autorisée, ou la taille maximale d’un
+    /// fichier FAT32 (`u32::MAX` octets).
+    FileTooLarge,
+
+    /// Une entrée portant ce nom existe déjà.
+    AlreadyExists,
+
+    /// Le numéro de cluster est hors de la zone de données (`2..=cluster_count + 1`).
+    InvalidCluster,
+
+    /// Le volume est monté en lecture seule.
+    ReadOnly,
+
+    /// La chaîne de clusters repasse par un cluster déjà visité.
+    CorruptChain,
+
+    /// L’image disque est plus courte que le volume décrit par le secteur de boot.
+    TruncatedImage,
+
+    /// Le secteur est illisible (bloc défectueux signalé par un [`super::ReadHook`]).
+    BadSector,
+
+    /// Les régions du volume (réservée, FAT, données) se chevauchent, laissent un trou
+    /// ou sortent de l’image.
+    BadLayout {
+        /// Région fautive : `"reserved"`, `"fat"` ou `"data"`.
+        region: &'static str,
+    },
+
+    /// La longueur de la chaîne de clusters ne correspond pas à la taille déclarée.
+    SizeMismatch {
+        /// Nombre de clusters attendu d’après la taille du fichier.
+        expected: u32,
+        /// Nombre de clusters présents dans la chaîne.
+        actual: u32,
+    },
+}
+
+impl fmt::Display for FsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            FsError::NotFound => "File not found",
+            FsError::NotAFile => "Not a file",
+            FsError::NotADirectory => "Not a directory",
+            FsError::InvalidUtf8 => "Invalid UTF-8 content",
+            FsError::OutOfBounds => "Error reading outbound",
+            FsError::DiskFull => "Disk full",
+            FsError::InvalidName => "Invalid file name",
+            FsError::ClusterReallocated => "Cluster already reallocated",
+            FsError::CorruptEntry => "Corrupt directory entry",
+            FsError::BadGeometry => "Bad volume geometry",
+            FsError::BootMismatch => "Backup boot sector mismatch",
+            FsError::FileTooLarge => "File too large",
+            FsError::AlreadyExists => "Entry already exists",
+            FsError::InvalidCluster => "Invalid cluster number",
+            FsError::ReadOnly => "Read-only file system",
+            FsError::CorruptChain => "Cyclic cluster chain",
+            FsError::TruncatedImage => "Truncated disk image",
+            FsError::BadSector => "Unreadable sector",
+            FsError::BadLayout { region } => {
+                return write!(f, "Bad volume layout: {} region", region);
+            }
+            FsError::SizeMismatch { expected, actual } => {
+                return write!(
+                    f,
+                    "Size mismatch: expected {} clusters, found {}",
+                    expected, actual
+                );
+            }
+        };
+        f.write_str(message)
+    }
+}
+
+impl From<FsError> for String {
+    fn from(error: FsError) -> String {
+        error.to_string()
+    }
+}
@@ -3,33 +3,89 @@
 //! Ce module fournit une interface de type *shell* permettant :
 //! - de lister le contenu d’un répertoire (`ls`),
 //! - de changer de répertoire (`cd`),
-//! - d’afficher le contenu d’un fichier texte (`cat`).
+//! - d’afficher le contenu d’un fichier texte (`cat`),
+//! - d’exécuter une ligne de commande (`run_command`).
 //!
 //! Il s’appuie sur [`Fat32FileSystem`] et les structures de haut niveau
 //! [`FileInfo`] pour abstraire le format FAT32
 
 use crate::{
-    file_system::{Fat32FileSystem, FileInfo, list_directory_entries},
-    print, println,
+    file_system::{
+        ATTR_ARCHIVE, ATTR_DIRECTORY, ATTR_HIDDEN, ATTR_READ_ONLY, ATTR_SYSTEM, Fat32FileSystem,
+        FileInfo, FsError, Progress, list_deleted, list_directory_entries,
+        list_directory_entries_raw,
+    },
+    print,
 };
 use alloc::{
+    boxed::Box,
+    format,
     rc::Rc,
     string::{String, ToString},
+    vec,
     vec::Vec,
 };
+use core::{cell::RefCell, fmt};
 use spin::Mutex;
 
+/// Nombre maximal de répertoires conservés dans l’historique de `cd`
+const HISTORY_LIMIT: usize = 32;
+
+/// Nombre de lignes affichées par la commande `more`
+const PAGE_LINES: usize = 24;
+
+/// Nombre d’octets par ligne d’un vidage hexadécimal (`xxd`)
+const HEXDUMP_WIDTH: usize = 16;
+
+/// Nombre d’octets affichés par la commande `xxd` sans limite explicite
+const XXD_BYTES: usize = 256;
+
+/// Rappel appelé avec chaque fragment de texte écrit par le shell (voir
+/// [`ShellSession::on_emit`])
+pub type EmitHook = Box<dyn Fn(&str)>;
+
 /// Représente une session de shell FAT32.
 ///
 /// Une session conserve
 /// - une référence partagée vers le système de fichiers
 /// - le cluster courant (équivalent du répertoire courant)
+/// - l’historique des répertoires visités (`cd -`)
 pub struct ShellSession {
     /// Système de fichiers FAT32 partagé
     fs: Rc<Mutex<Fat32FileSystem>>,
 
     /// Cluster courant (répertoire actif)
     pub current_cluster: u32,
+
+    /// Chemin absolu du répertoire courant
+    pub current_path: String,
+
+    /// Répertoires quittés par `cd`, du plus ancien au plus récent
+    history: Vec<(u32, String)>,
+
+    /// Largeur du terminal utilisée par `ls` pour aligner les entrées en colonnes
+    ///
+    /// `0` conserve l’affichage simple sur une ligne
+    pub terminal_width: usize,
+
+    /// Remplace les étiquettes `[DIR]`/`[FILE]` de `ls` par un marqueur suffixé au nom
+    /// (`/` répertoire, `*` fichier système, `@` étiquette de volume)
+    pub classify: bool,
+
+    /// Colore les noms affichés par `ls` avec des séquences ANSI
+    ///
+    /// Désactivé par défaut : la sortie VGA ou série ne les interprète pas forcément
+    pub color: bool,
+
+    /// Sortie capturée (`None` : affichage direct à l’écran)
+    output: RefCell<Option<String>>,
+
+    /// Rappel invoqué avec chaque fragment écrit par le shell, après son affichage ou sa
+    /// capture
+    ///
+    /// Le système de fichiers peut être verrouillé pendant l’appel (`ls`) : le rappel ne
+    /// doit pas le verrouiller à son tour
+    pub on_emit: Option<EmitHook>,
 }
 
 impl ShellSession {
@@ -41,6 +97,44 @@ impl ShellSession {
         ShellSession {
             fs,
             current_cluster,
+            current_path: "/".to_string(),
+            history: Vec::new(),
+            terminal_width: 0,
+            classify: false,
+            color: false,
+            output: RefCell::new(None),
+            on_emit: None,
+        }
+    }
+
+    /// Redirige la sortie du shell vers un tampon au lieu de l’écran
+    ///
+    /// Le contenu est récupéré avec [`Self::take_output`]
+    pub fn start_capture(&self) {
+        self.output.borrow_mut().get_or_insert_with(String::new);
+    }
+
+    /// Retourne et vide la sortie capturée depuis le dernier appel
+    pub fn take_output(&self) -> String {
+        self.output
+            .borrow_mut()
+            .as_mut()
+            .map(core::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Écrit sur la sortie du shell (écran ou tampon de capture), puis prévient
+    /// [`Self::on_emit`]
+    fn emit(&self, args: fmt::Arguments) {
+        match self.output.borrow_mut().as_mut() {
+            Some(buffer) => {
+                let _ = fmt::Write::write_fmt(buffer, args);
+            }
+            None => print!("{}", args),
+        }
+
+        if let Some(on_emit) = &self.on_emit {
+            on_emit(&format!("{}", args));
         }
     }
 
@@ -51,40 +145,157 @@ impl ShellSession {
     ///
     /// Les entrées spéciales `.` et `..` sont ignorées à l’affichage
     ///
+    /// Les entrées sont affichées au fur et à mesure de la lecture du répertoire (voir
+    /// [`Fat32FileSystem::dir_iter`]) : les premiers résultats apparaissent sans attendre
+    /// la fin d’un grand répertoire. Si [`Self::terminal_width`] est non nul, les entrées
+    /// sont triées et alignées en colonnes, les répertoires étant suffixés par `/` ; le
+    /// répertoire est alors lu en entier avant l’affichage
+    ///
+    /// Voir [`Self::classify`] et [`Self::color`] pour les marqueurs de type et la couleur
+    ///
+    /// # Errors
+    /// - [`FsError::NotFound`] si le chemin est invalide
+    /// - [`FsError::NotADirectory`] si le chemin désigne un fichier
+    /// - [`FsError::CorruptChain`] si la chaîne du répertoire est cyclique (les entrées
+    ///   lues avant le cluster répété sont affichées)
+    pub fn ls(&self, path: Option<&str>) -> Result<(), FsError> {
+        self.list(path, false)
+    }
+
+    /// Variante de [`Self::ls`] qui affiche aussi les entrées `.` et `..` (`ls -a`)
+    ///
+    /// La racine n’ayant pas d’entrées `.`/`..` sur le disque, elle s’affiche comme avec
+    /// [`Self::ls`]
+    ///
     /// # Errors
-    /// Retourne `"Entry not found"` si le chemin est invalide
-    pub fn ls(&self, path: Option<&str>) -> Result<(), &str> {
-        let cluster: u32;
+    /// Mêmes conditions que [`Self::ls`]
+    pub fn ls_all(&self, path: Option<&str>) -> Result<(), FsError> {
+        self.list(path, true)
+    }
 
-        match path {
+    /// Affichage commun à [`Self::ls`] et [`Self::ls_all`] (`all` conserve `.` et `..`)
+    fn list(&self, path: Option<&str>, all: bool) -> Result<(), FsError> {
+        let fs = self.fs.lock();
+
+        let cluster = match path {
             Some(p) => {
-                let file = self
-                    .fs
-                    .lock()
-                    .parse_path(p, Some(self.current_cluster))
-                    .ok_or("Entry not found")?;
+                let dir = fs.resolve(p, Some(self.current_cluster))?.file;
+                if !dir.is_directory {
+                    return Err(FsError::NotADirectory);
+                }
 
-                cluster = file.start_cluster;
+                dir.start_cluster
             }
-            None => cluster = self.current_cluster,
-        }
+            None => self.current_cluster,
+        };
+        let mut entries = fs.dir_iter(cluster);
+        let files = entries.by_ref().filter(|f| all || !f.is_dot_entry);
+
+        if self.terminal_width > 0 {
+            let mut labels: Vec<(String, String)> = files
+                .map(|f| {
+                    let marker = match self.classify {
+                        true => type_marker(&f),
+                        false if f.is_directory => "/",
+                        false => "",
+                    };
+                    (format!("{}{}", f.name, marker), self.ls_label(&f, marker))
+                })
+                .collect();
+            labels.sort();
 
-        let files = list_directory_entries(&self.fs.lock(), cluster);
+            let names: Vec<String> = labels.into_iter().map(|(_, label)| label).collect();
+            self.emit(format_args!(
+                "{}",
+                format_columns(&names, self.terminal_width)
+            ));
+            return entries.check_chain();
+        }
 
-        print!("> ");
-        for f in files.iter() {
-            if f.name == "." || f.name == ".." {
-                continue;
+        self.emit(format_args!("> "));
+        for f in files {
+            if self.classify {
+                self.emit(format_args!("{} ", self.ls_label(&f, type_marker(&f))));
+            } else {
+                let file_type = if f.is_directory { "[DIR]" } else { "[FILE]" };
+                self.emit(format_args!("{} {} ", file_type, self.ls_label(&f, "")));
             }
+        }
+        self.emit(format_args!("\n"));
+
+        entries.check_chain()
+    }
+
+    /// Nom d’une entrée affiché par `ls`, suivi de `marker` et coloré si [`Self::color`]
+    fn ls_label(&self, file: &FileInfo, marker: &str) -> String {
+        match ansi_color(file).filter(|_| self.color) {
+            Some(color) => format!("\x1b[{}m{}\x1b[0m{}", color, file.name, marker),
+            None => format!("{}{}", file.name, marker),
+        }
+    }
+
+    /// Liste brute des entrées d’un cluster de répertoire (`lscluster`)
+    ///
+    /// Outil d’analyse d’images corrompues : le cluster est lu directement, sans
+    /// résolution de chemin. Chaque entrée est affichée sur une ligne, y compris `.`,
+    /// `..`, l’étiquette du volume et les entrées supprimées
+    ///
+    /// # Errors
+    /// Retourne [`FsError::InvalidCluster`] si `cluster` n’est pas un cluster de données
+    pub fn lscluster(&self, cluster: u32) -> Result<(), FsError> {
+        let fs = self.fs.lock();
+        fs.cluster_to_sector(cluster)?;
+
+        for f in list_directory_entries_raw(&fs, cluster) {
+            let file_type = if f.is_volume_label {
+                "[LABEL]"
+            } else if f.is_directory {
+                "[DIR]"
+            } else {
+                "[FILE]"
+            };
+            self.emit(format_args!("{} {}\n", file_type, f.name));
+        }
 
-            let file_type = if f.is_directory { "[DIR]" } else { "[FILE]" };
-            print!("{} {} ", file_type, f.name);
+        for f in list_deleted(&fs, cluster) {
+            self.emit(format_args!("[DELETED] {}\n", f.name));
         }
-        print!("\n");
 
         Ok(())
     }
 
+    /// Affiche les plages de clusters libres (`freemap`), par exemple `10-250, 300-301`
+    ///
+    /// Permet de voir si un fichier peut encore être alloué de façon contiguë
+    pub fn freemap(&self) {
+        let fs = self.fs.lock();
+        let mut ranges: Vec<(u32, u32)> = Vec::new();
+
+        for cluster in fs.iter_free_clusters() {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == cluster => *end = cluster,
+                _ => ranges.push((cluster, cluster)),
+            }
+        }
+
+        let ranges: Vec<String> = ranges
+            .into_iter()
+            .map(|(start, end)| {
+                if start == end {
+                    format!("{}", start)
+                } else {
+                    format!("{}-{}", start, end)
+                }
+            })
+            .collect();
+
+        if ranges.is_empty() {
+            self.emit(format_args!("no free clusters\n"));
+        } else {
+            self.emit(format_args!("{}\n", ranges.join(", ")));
+        }
+    }
+
     /// Change le répertoire courant (`cd`)
     ///
     /// Le chemin peut être :
@@ -92,20 +303,42 @@ impl ShellSession {
     /// - relatif au répertoire courant
     ///
     /// # Errors
-    /// - `"Entry not found"` si le chemin est invalide
-    /// - `"Not a directory"` si la cible n’est pas un répertoire
-    pub fn cd(&mut self, path: &str) -> Result<(), &str> {
-        let file = self
-            .fs
-            .lock()
-            .parse_path(path, Some(self.current_cluster))
-            .ok_or("Entry not found")?;
+    /// - [`FsError::NotFound`] si le chemin est invalide
+    /// - [`FsError::NotADirectory`] si la cible n’est pas un répertoire
+    /// - [`FsError::CorruptEntry`] si l’entrée `.` est incohérente (mode strict)
+    pub fn cd(&mut self, path: &str) -> Result<(), FsError> {
+        let resolved = self.fs.lock().resolve(path, Some(self.current_cluster))?;
 
-        if !file.is_directory {
-            return Err("Not a directory");
+        if !resolved.file.is_directory {
+            return Err(FsError::NotADirectory);
         }
 
-        self.current_cluster = file.start_cluster;
+        let base = if path.starts_with('/') {
+            "/"
+        } else {
+            &self.current_path
+        };
+        let new_path = join_path(base, &resolved.components);
+
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+        let previous_path = core::mem::replace(&mut self.current_path, new_path);
+        self.history.push((self.current_cluster, previous_path));
+
+        self.current_cluster = resolved.file.start_cluster;
+        Ok(())
+    }
+
+    /// Revient au répertoire précédent (`cd -`)
+    ///
+    /// # Errors
+    /// Retourne [`FsError::NotFound`] si l’historique est vide
+    pub fn cd_back(&mut self) -> Result<(), FsError> {
+        let (cluster, path) = self.history.pop().ok_or(FsError::NotFound)?;
+
+        self.current_cluster = cluster;
+        self.current_path = path;
         Ok(())
     }
 
@@ -113,9 +346,56 @@ impl ShellSession {
     ///
     /// Les entrées spéciales `.` et `..` sont filtrées
     pub fn ls_entries(&self) -> Vec<FileInfo> {
+        self.ls_entries_where(|_| true)
+    }
+
+    /// Retourne toutes les entrées du répertoire courant, `.` et `..` compris (`ls -a`)
+    pub fn ls_entries_all(&self) -> Vec<FileInfo> {
+        list_directory_entries(&self.fs.lock(), self.current_cluster)
+    }
+
+    /// Retourne les entrées du répertoire courant qui satisfont `pred`
+    ///
+    /// Les entrées spéciales `.` et `..` sont toujours filtrées
+    pub fn ls_entries_where<F: Fn(&FileInfo) -> bool>(&self, pred: F) -> Vec<FileInfo> {
         list_directory_entries(&self.fs.lock(), self.current_cluster)
             .into_iter()
-            .filter(|f| f.name != "." && f.name != "..")
+            .filter(|f| !f.is_dot_entry)
+            .filter(|f| pred(f))
+            .collect()
+    }
+
+    /// Retourne les noms candidats pour compléter `partial` (complétion par tabulation)
+    ///
+    /// `partial` est découpé au dernier `/` : la partie gauche désigne le répertoire
+    /// (absolu, ou relatif au répertoire courant) et la partie droite le préfixe recherché,
+    /// sans tenir compte de la casse. Les répertoires sont suffixés par `/` ;
+    /// un répertoire introuvable ne donne aucun candidat
+    pub fn complete(&self, partial: &str) -> Vec<String> {
+        let (dir, prefix) = match partial.rfind('/') {
+            Some(idx) => (&partial[..=idx], &partial[idx + 1..]),
+            None => ("", partial),
+        };
+
+        let fs = self.fs.lock();
+        let Ok(handle) = fs.open_dir(dir, Some(self.current_cluster)) else {
+            return Vec::new();
+        };
+
+        handle
+            .iter()
+            .filter(|f| {
+                f.name
+                    .get(..prefix.len())
+                    .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+            })
+            .map(|f| {
+                if f.is_directory {
+                    format!("{}/", f.name)
+                } else {
+                    f.name
+                }
+            })
             .collect()
     }
 
@@ -124,17 +404,187 @@ impl ShellSession {
     /// Le contenu est affiché tel quel sur la sortie standard
     /// En cas d’erreur, le message est affiché à la place
     pub fn cat(&self, path: &str) -> Result<(), &str> {
-        let data = match self.fs.lock().read_file(path, None) {
-            Ok(content) => content,
-            Err(e) => e.to_string(),
+        if let Err(e) = self.cat_checked(path) {
+            self.emit(format_args!("{}\n", e));
+        }
+        Ok(())
+    }
+
+    /// Affiche le contenu de plusieurs fichiers à la suite (`cat a b`)
+    ///
+    /// Une erreur sur un fichier est affichée (`cat: <chemin>: <erreur>`) sans interrompre
+    /// l’affichage des suivants
+    ///
+    /// # Errors
+    /// Retourne la première erreur rencontrée
+    pub fn cat_many(&self, paths: &[&str]) -> Result<(), FsError> {
+        let mut first_error = None;
+
+        for path in paths {
+            if let Err(e) = self.cat_checked(path) {
+                self.emit(format_args!("cat: {}: {}\n", path, e));
+                first_error.get_or_insert(e);
+            }
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Affiche le contenu d’un fichier en propageant les erreurs
+    fn cat_checked(&self, path: &str) -> Result<(), FsError> {
+        let data = {
+            let fs = self.fs.lock();
+            let file = fs.resolve(path, Some(self.current_cluster))?.file;
+            fs.read_file_info(&file)?
         };
 
-        println!("{}", data);
+        let text = String::from_utf8(data).map_err(|_| FsError::InvalidUtf8)?;
+        self.emit(format_args!("{}\n", text));
+        Ok(())
+    }
+
+    /// Affiche au plus `max_lines` lignes d’un fichier (`more`)
+    ///
+    /// Le fichier est lu cluster par cluster sans être chargé en entier. S’il reste
+    /// du contenu après la dernière ligne affichée, `-- truncated --` est ajouté
+    ///
+    /// # Errors
+    /// - Erreurs de [`Fat32FileSystem::file_clusters`], y compris celles d’une lecture de
+    ///   cluster en cours d’affichage (les lignes précédentes restent affichées)
+    /// - [`FsError::InvalidUtf8`] si une ligne n’est pas de l’UTF-8 valide (les lignes
+    ///   précédentes restent affichées)
+    pub fn more(&self, path: &str, max_lines: usize) -> Result<(), FsError> {
+        let fs = self.fs.lock();
+        let clusters = fs.file_clusters(&self.absolute_path(path))?;
+
+        let mut printed = 0;
+        let mut line = Vec::new();
+
+        for cluster in clusters {
+            for byte in cluster? {
+                if printed == max_lines {
+                    self.emit(format_args!("-- truncated --\n"));
+                    return Ok(());
+                }
+
+                if byte == b'\n' {
+                    let text = core::str::from_utf8(&line).map_err(|_| FsError::InvalidUtf8)?;
+                    self.emit(format_args!("{}\n", text));
+                    printed += 1;
+                    line.clear();
+                } else {
+                    line.push(byte);
+                }
+            }
+        }
+
+        if !line.is_empty() {
+            let text = core::str::from_utf8(&line).map_err(|_| FsError::InvalidUtf8)?;
+            self.emit(format_args!("{}\n", text));
+        }
+
+        Ok(())
+    }
+
+    /// Affiche un vidage hexadécimal des `max_bytes` premiers octets d’un fichier (`xxd`)
+    ///
+    /// Le fichier est lu cluster par cluster (voir [`Fat32FileSystem::file_clusters`]) :
+    /// seuls le cluster et la ligne en cours sont conservés en mémoire
+    ///
+    /// # Errors
+    /// Erreurs de [`Fat32FileSystem::file_clusters`], y compris celles d’une lecture de
+    /// cluster en cours d’affichage (les lignes précédentes restent affichées)
+    pub fn xxd(&self, path: &str, max_bytes: usize) -> Result<(), FsError> {
+        let fs = self.fs.lock();
+        let clusters = fs.file_clusters(&self.absolute_path(path))?;
+
+        let mut offset = 0;
+        let mut remaining = max_bytes;
+        let mut line = Vec::with_capacity(HEXDUMP_WIDTH);
+
+        for cluster in clusters {
+            let cluster = cluster?;
+            for &byte in cluster.iter().take(remaining) {
+                line.push(byte);
+
+                if line.len() == HEXDUMP_WIDTH {
+                    self.emit(format_args!("{}\n", format_hexdump_line(offset, &line)));
+                    offset += line.len();
+                    line.clear();
+                }
+            }
+
+            remaining -= remaining.min(cluster.len());
+            if remaining == 0 {
+                break;
+            }
+        }
+
+        if !line.is_empty() {
+            self.emit(format_args!("{}\n", format_hexdump_line(offset, &line)));
+        }
+
+        Ok(())
+    }
+
+    /// Affiche un vidage hexadécimal du contenu brut d’un cluster (`xxd -c <cluster>`)
+    ///
+    /// Le décalage affiché est relatif au début du cluster
+    pub fn xxd_cluster(&self, cluster: u32) -> Result<(), FsError> {
+        let fs = self.fs.lock();
+        fs.cluster_to_sector(cluster)?;
+
+        for (i, line) in fs.read_cluster(cluster).chunks(HEXDUMP_WIDTH).enumerate() {
+            self.emit(format_args!(
+                "{}\n",
+                format_hexdump_line(i * HEXDUMP_WIDTH, line)
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Affiche les informations d’une entrée (`stat`)
+    ///
+    /// La taille logique est suivie de la taille allouée (clusters entiers), puis viennent
+    /// le cluster de départ, la longueur de la chaîne, les attributs (`RHSDA`) et les dates
+    pub fn stat(&self, path: &str) -> Result<(), FsError> {
+        let fs = self.fs.lock();
+        let file = fs.metadata(path, Some(self.current_cluster))?;
+
+        let cluster_bytes = (fs.bytes_per_sector * fs.sectors_per_cluster) as u64;
+        let allocated = (file.size as u64).div_ceil(cluster_bytes) * cluster_bytes;
+        let chain = fs.cluster_chain(file.start_cluster).len();
+
+        self.emit(format_args!("  File: {}\n", file.name));
+        self.emit(format_args!(
+            "  Type: {}\n",
+            if file.is_directory {
+                "directory"
+            } else {
+                "file"
+            }
+        ));
+        self.emit(format_args!(
+            "  Size: {} (allocated {})\n",
+            file.size, allocated
+        ));
+        self.emit(format_args!(
+            "Cluster: {} (chain of {} clusters)\n",
+            file.start_cluster, chain
+        ));
+        self.emit(format_args!(
+            " Attrs: {}\n",
+            format_attributes(file.attributes)
+        ));
+        self.emit(format_args!("Create: {}\n", file.created));
+        self.emit(format_args!("Modify: {}\n", file.modified));
+
         Ok(())
     }
 
     /// Crée un dossier (`mkdir`)
-    /// 
+    ///
     /// Renvoie un message en cas d'erreur
     pub fn mkdir(&self, parent_path: &str, folder_name: &str) -> Result<(), String> {
         let mut fs_lock = self.fs.lock();
@@ -145,7 +595,7 @@ impl ShellSession {
     }
 
     /// Crée un fichier (`touch`).
-    /// 
+    ///
     /// Renvoie un message en cas d'erreur
     pub fn touch(&self, parent_path: &str, file_name: &str) -> Result<(), String> {
         let mut fs_lock = self.fs.lock();
@@ -153,11 +603,421 @@ impl ShellSession {
         Ok(())
     }
 
+    /// Crée un fichier en créant au besoin ses répertoires parents (`touch -p`)
+    ///
+    /// Le chemin peut être absolu ou relatif au répertoire courant. Sans `-p`,
+    /// [`Self::touch`] échoue si le répertoire parent n’existe pas
+    pub fn touch_parents(&self, path: &str) -> Result<(), FsError> {
+        self.fs
+            .lock()
+            .create_file_with_parents(path, Some(self.current_cluster))
+    }
+
     /// Ecrit dans un fichier (équivalent d'un echo >>).
-    /// 
+    ///
     /// Renvoie un message en cas d'erreur
     pub fn write(&self, path: &str, text: &str) -> Result<(), String> {
         let mut fs = self.fs.lock();
-        fs.write_file(path, text.as_bytes())
+        Ok(fs.write_file(path, None, text.as_bytes())?)
+    }
+
+    /// Copie un fichier (`cp`, `cp -p` si `preserve` est activé).
+    ///
+    /// Les chemins peuvent être absolus ou relatifs au répertoire courant ;
+    /// `progress` est appelé après chaque cluster écrit (voir [`Fat32FileSystem::copy_file`])
+    pub fn cp(
+        &self,
+        src: &str,
+        dst: &str,
+        preserve: bool,
+        progress: Option<Progress<'_>>,
+    ) -> Result<(), FsError> {
+        self.fs
+            .lock()
+            .copy_file(src, dst, Some(self.current_cluster), preserve, progress)
+    }
+
+    /// Copie récursivement un répertoire (`cp -r`, voir [`Fat32FileSystem::copy_tree`])
+    pub fn cp_r(
+        &self,
+        src: &str,
+        dst: &str,
+        preserve: bool,
+        progress: Option<Progress<'_>>,
+    ) -> Result<(), FsError> {
+        self.fs
+            .lock()
+            .copy_tree(src, dst, Some(self.current_cluster), preserve, progress)
+    }
+
+    /// Déplace ou renomme une entrée (`mv`, voir [`Fat32FileSystem::move_entry`])
+    pub fn mv(&self, src: &str, dst: &str, progress: Option<Progress<'_>>) -> Result<(), FsError> {
+        self.fs
+            .lock()
+            .move_entry(src, dst, Some(self.current_cluster), progress)
+    }
+
+    /// Supprime un fichier (`rm`).
+    ///
+    /// Le chemin peut être absolu ou relatif au répertoire courant
+    pub fn rm(&self, path: &str) -> Result<(), FsError> {
+        self.fs.lock().remove_file(path, Some(self.current_cluster))
+    }
+
+    /// Supprime les fichiers du répertoire courant dont le nom correspond à `pattern`
+    /// (`rm *.tmp`, voir [`glob_match`]).
+    ///
+    /// Les répertoires et les entrées `.`/`..` sont ignorés. Une suppression en échec
+    /// n’interrompt pas les suivantes : seule la première erreur est retournée, une fois
+    /// tous les fichiers traités. Retourne le nombre de fichiers supprimés
+    ///
+    /// # Errors
+    /// - Première erreur de [`Fat32FileSystem::remove_file`] rencontrée
+    pub fn rm_glob(&self, pattern: &str) -> Result<usize, FsError> {
+        let mut fs = self.fs.lock();
+        let names: Vec<String> = fs
+            .dir_iter(self.current_cluster)
+            .filter(|f| !f.is_directory && !f.is_dot_entry && glob_match(pattern, &f.name))
+            .map(|f| f.name)
+            .collect();
+
+        let mut removed = 0;
+        let mut first_error = None;
+        for name in &names {
+            match fs.remove_file(name, Some(self.current_cluster)) {
+                Ok(()) => removed += 1,
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(removed),
+        }
     }
+
+    /// Exécute une ligne de commande
+    ///
+    /// Commandes reconnues : `ls [-a] [chemin]`, `cd <chemin>` (`cd -` pour revenir en arrière),
+    /// `cat <chemin>...`, `more <chemin>`, `lscluster <cluster>`, `mkdir <nom>`, `touch [-p] <nom>`, `write <chemin> <texte>`, `rm <chemin|motif>`, `stat <chemin>`,
+    /// `xxd <chemin> [octets]`, `xxd -c <cluster>`, `freemap`,
+    /// `cp [-r] [-p] <source> <destination>` et `mv <source> <destination>`.
+    /// Les créations ont lieu dans le répertoire courant
+    ///
+    /// # Errors
+    /// Retourne le message d’erreur de la commande, ou `"unknown command"`
+    pub fn run_command(&mut self, line: &str) -> Result<(), String> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(());
+        };
+        let argument = words.next();
+        let missing = || "missing operand".to_string();
+
+        match command {
+            "ls" => match argument {
+                Some("-a") => Ok(self.ls_all(words.next())?),
+                path => Ok(self.ls(path)?),
+            },
+            "cd" => match argument.ok_or_else(missing)? {
+                "-" => Ok(self.cd_back()?),
+                path => Ok(self.cd(path)?),
+            },
+            "cat" => {
+                let mut paths = vec![argument.ok_or_else(missing)?];
+                paths.extend(words);
+
+                if paths.len() == 1 {
+                    Ok(self.cat_checked(paths[0])?)
+                } else {
+                    // Les erreurs sont déjà affichées fichier par fichier
+                    let _ = self.cat_many(&paths);
+                    Ok(())
+                }
+            }
+            "lscluster" => {
+                let cluster = argument
+                    .ok_or_else(missing)?
+                    .parse()
+                    .map_err(|_| "invalid cluster".to_string())?;
+                Ok(self.lscluster(cluster)?)
+            }
+            "freemap" => {
+                self.freemap();
+                Ok(())
+            }
+            "more" => Ok(self.more(argument.ok_or_else(missing)?, PAGE_LINES)?),
+            "mkdir" => self.mkdir(&self.current_path.clone(), argument.ok_or_else(missing)?),
+            "touch" => match argument.ok_or_else(missing)? {
+                "-p" => Ok(self.touch_parents(words.next().ok_or_else(missing)?)?),
+                name => self.touch(&self.current_path.clone(), name),
+            },
+            "write" => {
+                let path = self.absolute_path(argument.ok_or_else(missing)?);
+                let text: Vec<&str> = words.collect();
+                self.write(&path, &text.join(" "))
+            }
+            "rm" => match argument.ok_or_else(missing)? {
+                pattern if pattern.contains(['*', '?']) => {
+                    self.rm_glob(pattern)?;
+                    Ok(())
+                }
+                path => Ok(self.rm(path)?),
+            },
+            "stat" => Ok(self.stat(argument.ok_or_else(missing)?)?),
+            "xxd" => match argument.ok_or_else(missing)? {
+                "-c" => {
+                    let cluster = words
+                        .next()
+                        .ok_or_else(missing)?
+                        .parse()
+                        .map_err(|_| "invalid cluster".to_string())?;
+                    Ok(self.xxd_cluster(cluster)?)
+                }
+                path => {
+                    let max_bytes = match words.next() {
+                        Some(count) => count.parse().map_err(|_| "invalid length".to_string())?,
+                        None => XXD_BYTES,
+                    };
+                    Ok(self.xxd(path, max_bytes)?)
+                }
+            },
+            "cp" => {
+                let (mut recursive, mut preserve) = (false, false);
+                let mut operands = Vec::new();
+
+                for word in argument.into_iter().chain(words) {
+                    match word.strip_prefix('-') {
+                        Some(flags) if !flags.is_empty() => {
+                            for flag in flags.chars() {
+                                match flag {
+                                    'r' => recursive = true,
+                                    'p' => preserve = true,
+                                    _ => return Err(format!("invalid option -- '{}'", flag)),
+                                }
+                            }
+                        }
+                        _ => operands.push(word),
+                    }
+                }
+
+                let &[src, dst] = operands.as_slice() else {
+                    return Err(missing());
+                };
+
+                if recursive {
+                    Ok(self.cp_r(src, dst, preserve, None)?)
+                } else {
+                    Ok(self.cp(src, dst, preserve, None)?)
+                }
+            }
+            "mv" => {
+                let src = argument.ok_or_else(missing)?;
+                let dst = words.next().ok_or_else(missing)?;
+                Ok(self.mv(src, dst, None)?)
+            }
+            _ => Err("unknown command".to_string()),
+        }
+    }
+
+    /// Retourne l’invite du shell, par exemple `fat32:/test_dir> `
+    ///
+    /// Une boucle interactive affiche cette invite, lit une ligne sur la console série
+    /// puis la transmet à [`Self::run_command_interactive`], qui affiche les erreurs
+    /// sans interrompre la boucle
+    pub fn prompt(&self) -> String {
+        format!("fat32:{}> ", self.current_path)
+    }
+
+    /// Exécute une ligne de commande et affiche l’erreur éventuelle (`<cmd>: <erreur>`)
+    /// au lieu de la propager, comme un shell interactif
+    pub fn run_command_interactive(&mut self, line: &str) {
+        if let Err(e) = self.run_command(line) {
+            let command = line.split_whitespace().next().unwrap_or("");
+            self.emit(format_args!("{}: {}\n", command, e));
+        }
+    }
+
+    /// Convertit un chemin relatif au répertoire courant en chemin absolu
+    fn absolute_path(&self, path: &str) -> String {
+        if path.starts_with('/') {
+            path.to_string()
+        } else {
+            join_path(&self.current_path, &[path.to_string()])
+        }
+    }
+}
+
+/// Formate une ligne de vidage hexadécimal à la manière de `xxd`
+///
+/// `00000000: 7465 7374 0a                             test.` : décalage, octets par
+/// groupes de deux, puis leur représentation ASCII (`.` pour un octet non imprimable)
+pub fn format_hexdump_line(offset: usize, bytes: &[u8]) -> String {
+    let mut line = format!("{:08x}:", offset);
+
+    for i in 0..HEXDUMP_WIDTH {
+        if i % 2 == 0 {
+            line.push(' ');
+        }
+        match bytes.get(i) {
+            Some(byte) => line.push_str(&format!("{:02x}", byte)),
+            None => line.push_str("  "),
+        }
+    }
+
+    line.push_str("  ");
+    line.extend(bytes.iter().map(|&byte| {
+        if byte.is_ascii_graphic() || byte == b' ' {
+            byte as char
+        } else {
+            '.'
+        }
+    }));
+
+    line
+}
+
+/// Formate un octet d’attributs FAT en `RHSDA`, un `-` remplaçant chaque attribut absent
+fn format_attributes(attributes: u8) -> String {
+    [
+        (ATTR_READ_ONLY, 'R'),
+        (ATTR_HIDDEN, 'H'),
+        (ATTR_SYSTEM, 'S'),
+        (ATTR_DIRECTORY, 'D'),
+        (ATTR_ARCHIVE, 'A'),
+    ]
+    .iter()
+    .map(|&(flag, letter)| if attributes & flag != 0 { letter } else { '-' })
+    .collect()
+}
+
+/// Marqueur de type suffixé au nom par `ls` (voir [`ShellSession::classify`])
+///
+/// `/` pour un répertoire, `@` pour l’étiquette du volume, `*` pour un fichier portant
+/// l’attribut système, rien pour un fichier ordinaire
+pub fn type_marker(file: &FileInfo) -> &'static str {
+    if file.is_directory {
+        "/"
+    } else if file.is_volume_label {
+        "@"
+    } else if file.attributes & ATTR_SYSTEM != 0 {
+        "*"
+    } else {
+        ""
+    }
+}
+
+/// Code de couleur ANSI (SGR) d’une entrée, `None` pour un fichier ordinaire
+fn ansi_color(file: &FileInfo) -> Option<&'static str> {
+    if file.is_directory {
+        Some("1;34")
+    } else if file.is_volume_label {
+        Some("33")
+    } else if file.attributes & ATTR_SYSTEM != 0 {
+        Some("31")
+    } else {
+        None
+    }
+}
+
+/// Largeur affichée d’un nom, sans les séquences de couleur ANSI (`\x1b[...m`)
+fn display_width(name: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+
+    for c in name.chars() {
+        match c {
+            '\x1b' => in_escape = true,
+            'm' if in_escape => in_escape = false,
+            _ if in_escape => {}
+            _ => width += 1,
+        }
+    }
+
+    width
+}
+
+/// Indique si `name` correspond au motif `pattern`
+///
+/// `*` remplace une suite quelconque de caractères (éventuellement vide), `?` un seul
+/// caractère. Les noms 8.3 étant stockés en majuscules (`touch a.tmp` crée `A.TMP`),
+/// la comparaison ignore la casse ASCII, comme sous DOS
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position du dernier `*` rencontré et du caractère du nom où il a été essayé
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c.eq_ignore_ascii_case(&name[n]) => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, tried)) => {
+                    backtrack = Some((star, tried + 1));
+                    p = star + 1;
+                    n = tried + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Applique des composants de chemin normalisés (`..` compris) à un chemin absolu
+fn join_path(base: &str, components: &[String]) -> String {
+    let mut parts: Vec<&str> = base.split('/').filter(|s| !s.is_empty()).collect();
+
+    for component in components {
+        if component == ".." {
+            parts.pop();
+        } else {
+            parts.push(component);
+        }
+    }
+
+    format!("/{}", parts.join("/"))
+}
+
+/// Répartit des noms en colonnes alignées tenant dans `width` caractères (comme GNU `ls`)
+///
+/// Les noms sont lus colonne par colonne, chaque colonne étant complétée par des espaces
+/// jusqu’au nom le plus long plus deux. Chaque ligne se termine par `\n`, sans espaces finaux
+///
+/// Les séquences de couleur ANSI (voir [`ShellSession::color`]) n’occupent aucune colonne
+pub fn format_columns(names: &[String], width: usize) -> String {
+    if names.is_empty() {
+        return String::new();
+    }
+
+    let column_width = names.iter().map(|n| display_width(n)).max().unwrap_or(0) + 2;
+    let columns = (width / column_width).max(1);
+    let rows = names.len().div_ceil(columns);
+
+    let mut output = String::new();
+    for row in 0..rows {
+        let line: Vec<&String> = names.iter().skip(row).step_by(rows).collect();
+
+        for (i, name) in line.iter().enumerate() {
+            output.push_str(name);
+            if i + 1 < line.len() {
+                let padding = column_width - display_width(name);
+                output.extend(core::iter::repeat_n(' ', padding));
+            }
+        }
+        output.push('\n');
+    }
+
+    output
 }
@@ -0,0 +1,83 @@
+//! Options de montage d’un volume FAT32
+//!
+//! [`MountOptions`] regroupe les réglages de tolérance et de performance appliqués
+//! une fois pour toutes par [`Fat32FileSystem::new_with_options`], plutôt qu’à chaque appel.
+
+use super::{DEFAULT_MAX_READ_BYTES, Fat32FileSystem};
+
+/// Stratégie de recherche d’un cluster libre (voir [`MountOptions::alloc_strategy`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AllocStrategy {
+    /// Recherche toujours depuis le cluster 2 : les trous laissés par les suppressions
+    /// sont comblés en premier, au prix de fichiers fragmentés.
+    FirstFit,
+    /// Reprend la recherche après le dernier cluster alloué (le FSInfo fournit le point
+    /// de départ) : les allocations successives restent contiguës et le parcours est plus
+    /// court, au prix de trous laissés derrière le curseur. Stratégie par défaut.
+    #[default]
+    NextFit,
+}
+
+/// Réglages appliqués au montage d’un volume.
+///
+/// Les valeurs par défaut correspondent au comportement de [`Fat32FileSystem::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MountOptions {
+    /// Complète par des zéros les lectures au-delà de la fin de l’image
+    /// au lieu d’échouer (images tronquées). Désactivé par défaut.
+    pub allow_short_reads: bool,
+    /// Nombre maximal de clusters contigus lus en une seule requête lors du parcours
+    /// d’une chaîne. `0` ou `1` lit les clusters un par un.
+    pub readahead_clusters: u32,
+    /// Vérifie, lors de l’entrée dans un répertoire, que son entrée `.` pointe sur
+    /// le cluster atteint. Désactivé par défaut.
+    pub strict_dot_entries: bool,
+    /// Borne la lecture d’un fichier à `min(size, longueur de la chaîne)` et ne lit
+    /// que les clusters couvrant cette longueur (taille déclarée obsolète). Désactivé par défaut.
+    pub clamp_size_to_chain: bool,
+    /// Nombre maximal d’octets accumulés lors de la lecture d’un fichier, afin qu’une
+    /// taille déclarée absurde ne puisse pas épuiser le tas du noyau.
+    pub max_read_bytes: usize,
+    /// Monte le volume en lecture seule : toutes les méthodes d’écriture (`create_file`,
+    /// `mkdir`, `write_file`, `remove_file`…) échouent avec [`FsError::ReadOnly`](super::FsError::ReadOnly) avant de
+    /// toucher au disque. Indépendant de l’attribut lecture seule des fichiers.
+    pub read_only: bool,
+    /// Refuse au montage une image dont la longueur n’est pas un multiple de la taille de
+    /// secteur ou qui est tronquée (voir [`Fat32FileSystem::try_new_with_options`]).
+    /// Désactivé par défaut : l’anomalie est seulement signalée par [`Fat32FileSystem::check`].
+    pub strict_geometry: bool,
+    /// Remplit de zéros chaque cluster libéré (`rm`, réécriture plus courte, compactage)
+    /// avant de le marquer libre, pour que son contenu ne soit pas récupérable.
+    /// Désactivé par défaut (comportement FAT standard, plus rapide).
+    pub wipe_on_free: bool,
+    /// Stratégie de recherche des clusters libres (next-fit par défaut).
+    pub alloc_strategy: AllocStrategy,
+}
+
+impl Default for MountOptions {
+    fn default() -> Self {
+        MountOptions {
+            allow_short_reads: false,
+            readahead_clusters: 0,
+            strict_dot_entries: false,
+            clamp_size_to_chain: false,
+            max_read_bytes: DEFAULT_MAX_READ_BYTES,
+            read_only: false,
+            strict_geometry: false,
+            wipe_on_free: false,
+            alloc_strategy: AllocStrategy::NextFit,
+        }
+    }
+}
+
+impl Fat32FileSystem {
+    /// Retourne les options actuellement appliquées au volume.
+    pub fn mount_options(&self) -> MountOptions {
+        self.options
+    }
+
+    /// Applique un jeu d’options au volume monté.
+    pub fn set_mount_options(&mut self, opts: MountOptions) {
+        self.options = opts;
+    }
+}
@@ -0,0 +1,153 @@
+//! Import et export d’arborescences complètes
+//!
+//! [`Fat32FileSystem::import_tree`] peuple un volume à partir de paires `(chemin, contenu)`
+//! fournies par l’hôte, ce qui permet à l’outillage et aux tests de construire une image
+//! sans la fabriquer à l’extérieur ; [`Fat32FileSystem::export_tree`] en est l’inverse.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{Fat32FileSystem, FsError, to_short_name};
+
+impl Fat32FileSystem {
+    /// Importe une liste de fichiers `(chemin, contenu)` dans le volume.
+    ///
+    /// Les chemins sont relatifs à la racine. Les répertoires intermédiaires sont créés
+    /// au besoin (`mkdir -p`) et réutilisés s’ils existent déjà. Tous les noms sont validés
+    /// avant la première écriture ; les entrées sont ensuite importées dans l’ordre et
+    /// celles qui précèdent un échec restent en place.
+    ///
+    /// Un fichier dont le contenu ne tient pas sur le volume est supprimé : aucun fichier
+    /// tronqué ne reste dans la destination. Les répertoires créés pour lui restent en
+    /// place (vides s’ils ne contiennent rien d’autre).
+    ///
+    /// # Errors
+    /// Retourne l’index de l’entrée fautive accompagné de :
+    /// - [`FsError::ReadOnly`] si le volume est monté en lecture seule (index `0`)
+    /// - [`FsError::InvalidName`] si un composant n’est pas un nom 8.3 valide (rien n’est écrit)
+    /// - [`FsError::NotADirectory`] si un composant intermédiaire est un fichier
+    /// - [`FsError::AlreadyExists`] si le fichier existe déjà
+    /// - [`FsError::DiskFull`] si l’espace ou le répertoire parent est saturé
+    pub fn import_tree(&mut self, entries: &[(&str, &[u8])]) -> Result<(), (usize, FsError)> {
+        self.ensure_writable().map_err(|e| (0, e))?;
+
+        let mut parsed = Vec::with_capacity(entries.len());
+
+        for (i, (path, _)) in entries.iter().enumerate() {
+            let names = path
+                .split('/')
+                .filter(|c| !c.is_empty())
+                .map(|c| to_short_name(c).ok_or(FsError::InvalidName))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| (i, e))?;
+
+            if names.is_empty() {
+                return Err((i, FsError::InvalidName));
+            }
+            parsed.push(names);
+        }
+
+        for (i, (names, (_, data))) in parsed.iter().zip(entries).enumerate() {
+            self.import_file(names, data).map_err(|e| (i, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Exporte tout le contenu du volume sous forme de paires `(chemin absolu, contenu)`.
+    ///
+    /// Les répertoires apparaissent avec un `/` final et un contenu vide, afin qu’un
+    /// répertoire vide survive à un aller-retour. L’ordre est celui de [`Self::walk`].
+    /// Les fichiers illisibles (chaîne corrompue, taille excessive) sont omis.
+    pub fn export_tree(&self) -> Vec<(String, Vec<u8>)> {
+        let mut entries = Vec::new();
+
+        self.walk(
+            |_| false,
+            |path, file| {
+                if file.is_directory {
+                    entries.push((format!("{}/", path), Vec::new()));
+                } else if let Ok(data) = self.read_file_info(file) {
+                    entries.push((path.into(), data));
+                }
+            },
+        );
+
+        entries
+    }
+
+    /// Crée un fichier vide en créant au besoin ses répertoires parents (`touch -p`).
+    ///
+    /// `path` est absolu ou relatif au répertoire `current_cluster`. Comme pour
+    /// [`Self::import_tree`], les répertoires manquants sont créés et ceux qui existent
+    /// sont réutilisés ; tous les noms sont validés avant la première écriture.
+    ///
+    /// # Errors
+    /// - [`FsError::ReadOnly`] si le volume est monté en lecture seule
+    /// - [`FsError::InvalidName`] si un composant n’est pas un nom 8.3 valide (rien n’est écrit)
+    /// - [`FsError::NotADirectory`] si un composant intermédiaire est un fichier
+    /// - [`FsError::AlreadyExists`] si le fichier existe déjà
+    /// - [`FsError::DiskFull`] si l’espace ou un répertoire parent est saturé
+    pub fn create_file_with_parents(
+        &mut self,
+        path: &str,
+        current_cluster: Option<u32>,
+    ) -> Result<(), FsError> {
+        self.ensure_writable()?;
+
+        let names = path
+            .split('/')
+            .filter(|c| !c.is_empty())
+            .map(|c| to_short_name(c).ok_or(FsError::InvalidName))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (&file_name, dirs) = names.split_last().ok_or(FsError::InvalidName)?;
+
+        let start = if path.starts_with('/') {
+            self.root_cluster
+        } else {
+            current_cluster.unwrap_or(self.root_cluster)
+        };
+        let cluster = self.create_parents(start, dirs)?;
+
+        if self.find_child(cluster, file_name).is_some() {
+            return Err(FsError::AlreadyExists);
+        }
+
+        self.create_file_in(cluster, file_name, &[])?;
+        Ok(())
+    }
+
+    /// Crée le fichier désigné par `names` (noms courts depuis la racine) et y écrit `data`.
+    fn import_file(&mut self, names: &[[u8; 11]], data: &[u8]) -> Result<(), FsError> {
+        let (&file_name, dirs) = names.split_last().ok_or(FsError::InvalidName)?;
+
+        let cluster = self.create_parents(self.root_cluster, dirs)?;
+
+        if self.find_child(cluster, file_name).is_some() {
+            return Err(FsError::AlreadyExists);
+        }
+
+        let location = self.create_file_in(cluster, file_name, &[])?;
+        if let Err(error) = self.append_at(&location, data) {
+            self.discard_entry(cluster, &location)?;
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Descend depuis `cluster` le long de `dirs` (noms courts), en créant les répertoires
+    /// manquants, et retourne le cluster du dernier.
+    fn create_parents(&mut self, mut cluster: u32, dirs: &[[u8; 11]]) -> Result<u32, FsError> {
+        for &name in dirs {
+            cluster = match self.find_child(cluster, name) {
+                Some(dir) if dir.is_directory => self.dir_cluster(&dir),
+                Some(_) => return Err(FsError::NotADirectory),
+                None => self.mkdir_in(cluster, name, &[])?,
+            };
+        }
+
+        Ok(cluster)
+    }
+}
@@ -10,8 +10,15 @@ use alloc::rc::Rc;
 use alloc::string::ToString;
 use bootloader::{BootInfo, entry_point};
 use core::panic::PanicInfo;
-use fat32_impl::file_system::{Fat32FileSystem, interface::ShellSession};
-use fat32_impl::file_system::{list_directory_entries, list_files_names};
+use fat32_impl::file_system::{
+    ATTR_SYSTEM, AllocStrategy, CheckOptions, Fat32FileSystem, FatClock, FatDir, FatTimestamp,
+    FileInfo, FsError, FsWarning, LongFileName, MountOptions, ReadHook,
+    interface::{ShellSession, format_columns, format_hexdump_line, glob_match, type_marker},
+};
+use fat32_impl::file_system::{
+    list_deleted, list_directory_entries, list_directory_entries_raw, list_files_names,
+    try_list_directory_entries,
+};
 use spin::Mutex;
 
 entry_point!(main);
@@ -21,12 +28,154 @@ entry_point!(main);
 // [test_dir] > test_dir_file
 const DISK_IMAGE: &[u8] = include_bytes!("./test.img");
 
-//TODO Trouver une méthode plus optimisée pour charger le file system une seule fois
+// L’image, intégrée une seule fois au binaire de test, est recopiée sur un périphérique
+// de blocs en mémoire : chaque test modifie sa propre copie
 fn init_fs() -> Rc<Mutex<Fat32FileSystem>> {
-    let disk_box = alloc::vec::Vec::from(DISK_IMAGE).into_boxed_slice();
-    let fs = Fat32FileSystem::new(disk_box);
+    init_fs_with_hook().0
+}
+
+// Comme `init_fs`, en conservant l’observateur des lectures du périphérique
+fn init_fs_with_hook() -> (Rc<Mutex<Fat32FileSystem>>, Rc<DeviceHook>) {
+    let device = MemBlockDevice::from_image(DISK_IMAGE);
+    let hook = device.hook.clone();
+    (Rc::new(Mutex::new(device.mount())), hook)
+}
+
+// Observateur des lectures d’un `MemBlockDevice` : compte les lectures de blocs, et les
+// secteurs marqués défectueux échouent avec `BadSector`, comme sur un support endommagé
+#[derive(Debug, Default)]
+struct DeviceHook {
+    reads: core::cell::Cell<u64>,
+    bad_sectors: core::cell::RefCell<alloc::collections::BTreeSet<u32>>,
+}
+
+impl DeviceHook {
+    // Observe un volume construit sans `MemBlockDevice` (`new_from_parts`, `blank_volume`)
+    fn attach(fs: &mut Fat32FileSystem) -> Rc<Self> {
+        let hook = Rc::new(DeviceHook::default());
+        fs.set_read_hook(hook.clone());
+        hook
+    }
+
+    fn reads(&self) -> u64 {
+        self.reads.get()
+    }
+
+    fn fail_sector(&self, sector: u32) {
+        self.bad_sectors.borrow_mut().insert(sector);
+    }
+}
+
+impl ReadHook for DeviceHook {
+    fn before_read(&self, start: u32, count: u32) -> Result<(), FsError> {
+        self.reads.set(self.reads.get() + 1);
+        match self.bad_sectors.borrow().range(start..start + count).next() {
+            Some(_) => Err(FsError::BadSector),
+            None => Ok(()),
+        }
+    }
+}
+
+// Périphérique de blocs en mémoire (secteurs de 512 octets) : les écritures modifient le
+// tampon interne, monté comme image disque puis récupéré au démontage pour être relu
+struct MemBlockDevice {
+    data: alloc::vec::Vec<u8>,
+    hook: Rc<DeviceHook>,
+}
+
+impl MemBlockDevice {
+    const BLOCK_SIZE: usize = 512;
+
+    // Périphérique vierge de `size` octets
+    fn new(size: usize) -> Self {
+        MemBlockDevice {
+            data: alloc::vec![0u8; size],
+            hook: Rc::default(),
+        }
+    }
+
+    fn from_image(image: &[u8]) -> Self {
+        MemBlockDevice {
+            data: image.to_vec(),
+            hook: Rc::default(),
+        }
+    }
 
-    Rc::new(Mutex::new(fs))
+    fn read_block(&self, lba: u32) -> &[u8] {
+        let offset = lba as usize * Self::BLOCK_SIZE;
+        &self.data[offset..offset + Self::BLOCK_SIZE]
+    }
+
+    fn write_block(&mut self, lba: u32, block: &[u8; Self::BLOCK_SIZE]) {
+        let offset = lba as usize * Self::BLOCK_SIZE;
+        self.data[offset..offset + Self::BLOCK_SIZE].copy_from_slice(block);
+    }
+
+    fn mount(self) -> Fat32FileSystem {
+        let mut fs = Fat32FileSystem::new(self.data.into_boxed_slice());
+        fs.set_read_hook(self.hook);
+        fs
+    }
+
+    fn unmount(fs: Fat32FileSystem) -> Self {
+        MemBlockDevice {
+            data: fs.disk.into_vec(),
+            hook: Rc::default(),
+        }
+    }
+}
+
+// Écrit une entrée FAT d’un volume synthétique (FAT au secteur 1, secteurs de 512 octets)
+fn set_fat_entry(disk: &mut [u8], cluster: u32, value: u32) {
+    let offset = 512 + cluster as usize * 4;
+    disk[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+// Formate un volume FAT32 vierge de `size` octets sur un [`MemBlockDevice`] (secteurs de
+// 512 octets, un secteur par cluster, 32 secteurs réservés, deux FAT, racine au cluster 2)
+fn blank_volume(size: usize) -> Fat32FileSystem {
+    let total_sectors = (size / 512) as u32;
+    let sectors_per_fat = (total_sectors * 4).div_ceil(512);
+    let data_sector = 32 + 2 * sectors_per_fat;
+    let clusters = (total_sectors - data_sector).min(sectors_per_fat * 128 - 2);
+    let mut device = MemBlockDevice::new(size);
+
+    let mut boot = [0u8; 512];
+    boot[0..3].copy_from_slice(&[0xEB, 0x58, 0x90]);
+    boot[3..11].copy_from_slice(b"FAT32IMP");
+    boot[11..13].copy_from_slice(&512u16.to_le_bytes());
+    boot[13] = 1;
+    boot[14..16].copy_from_slice(&32u16.to_le_bytes());
+    boot[16] = 2;
+    boot[21] = 0xF8;
+    boot[32..36].copy_from_slice(&total_sectors.to_le_bytes());
+    boot[36..40].copy_from_slice(&sectors_per_fat.to_le_bytes());
+    boot[44..48].copy_from_slice(&2u32.to_le_bytes());
+    boot[48..50].copy_from_slice(&1u16.to_le_bytes());
+    boot[50..52].copy_from_slice(&6u16.to_le_bytes());
+    boot[82..90].copy_from_slice(b"FAT32   ");
+    boot[510..512].copy_from_slice(&[0x55, 0xAA]);
+    device.write_block(0, &boot);
+    device.write_block(6, &boot);
+
+    // FSInfo : tous les clusters sont libres sauf la racine
+    let mut fsinfo = [0u8; 512];
+    fsinfo[0..4].copy_from_slice(&0x41615252u32.to_le_bytes());
+    fsinfo[484..488].copy_from_slice(&0x61417272u32.to_le_bytes());
+    fsinfo[488..492].copy_from_slice(&(clusters - 1).to_le_bytes());
+    fsinfo[492..496].copy_from_slice(&3u32.to_le_bytes());
+    fsinfo[508..512].copy_from_slice(&0xAA550000u32.to_le_bytes());
+    device.write_block(1, &fsinfo);
+
+    let mut fat = [0u8; 512];
+    for (cluster, value) in [0x0FFFFFF8u32, 0x0FFFFFFF, 0x0FFFFFFF].iter().enumerate() {
+        fat[cluster * 4..cluster * 4 + 4].copy_from_slice(&value.to_le_bytes());
+    }
+    for copy in 0..2 {
+        device.write_block(32 + copy * sectors_per_fat, &fat);
+    }
+
+    device.mount()
 }
 
 #[test_case]
@@ -153,6 +302,2933 @@ fn init_test() {
     );
 }
 
+#[test_case]
+fn cross_links_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+
+    assert!(fs_lock.find_cross_links().is_empty());
+
+    // Copie de l’entrée de test.txt (2e slot de la racine) vers un slot libre
+    let root_offset = (fs_lock.data_sector * fs_lock.bytes_per_sector) as usize;
+    let mut entry = [0u8; 32];
+    entry.copy_from_slice(&fs_lock.disk[root_offset + 32..root_offset + 64]);
+    entry[0..11].copy_from_slice(b"COPY    TXT");
+    fs_lock.disk[root_offset + 6 * 32..root_offset + 7 * 32].copy_from_slice(&entry);
+
+    let links = fs_lock.find_cross_links();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].1, ["/test.txt", "/COPY.TXT"]);
+}
+
+#[test_case]
+fn synthetic_volume_test() {
+    // Volume de 4 clusters : secteur 0 réservé, FAT au secteur 1, données à partir du secteur 2
+    let mut disk = alloc::vec![0u8; 6 * 512];
+
+    let fat = 512;
+    disk[fat + 8..fat + 12].copy_from_slice(&0x0FFFFFFFu32.to_le_bytes());
+    disk[fat + 12..fat + 16].copy_from_slice(&0x0FFFFFFFu32.to_le_bytes());
+    disk[fat + 16..fat + 20].copy_from_slice(&0x0FFFFFFFu32.to_le_bytes());
+
+    let root = 2 * 512;
+    disk[root..root + 11].copy_from_slice(b"HELLO   TXT");
+    disk[root + 11] = 0x20;
+    disk[root + 26..root + 28].copy_from_slice(&3u16.to_le_bytes());
+    disk[root + 28..root + 32].copy_from_slice(&5u32.to_le_bytes());
+    disk[root + 32..root + 43].copy_from_slice(b"SUB        ");
+    disk[root + 43] = 0x10;
+    disk[root + 58..root + 60].copy_from_slice(&4u16.to_le_bytes());
+    disk[3 * 512..3 * 512 + 5].copy_from_slice(b"hello");
+
+    let fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+
+    let files = list_directory_entries(&fs, fs.root_cluster);
+    assert_eq!(["HELLO.TXT", "SUB"], list_files_names(&files).as_slice());
+    assert!(!files[0].is_directory);
+    assert_eq!(files[0].start_cluster, 3);
+    assert!(files[1].is_directory);
+    assert_eq!(files[1].start_cluster, 4);
+    assert_eq!(fs.cluster_chain(3), [3]);
+    assert_eq!(fs.read_file("/HELLO.TXT", None), Ok("hello".to_string()));
+}
+
+#[test_case]
+fn dotdot_root_cluster_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let test_dir = list_directory_entries(&fs_lock, fs_lock.root_cluster)
+        .into_iter()
+        .find(|f| f.name == "test_dir")
+        .unwrap();
+
+    // Le `..` de test_dir est stocké avec le cluster 0 sur le disque
+    let entries = list_directory_entries(&fs_lock, test_dir.start_cluster);
+    let dotdot = entries.iter().find(|f| f.name == "..").unwrap();
+    assert_eq!(dotdot.start_cluster, fs_lock.root_cluster);
+}
+
+#[test_case]
+fn read_file_info_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let root = list_directory_entries(&fs_lock, fs_lock.root_cluster);
+    let test_dir = root.iter().find(|f| f.name == "test_dir").unwrap();
+
+    let entries = fs_lock.list_dir_info(test_dir);
+    let file = entries.iter().find(|f| f.name == "test_dir_file").unwrap();
+
+    let data = fs_lock.read_file_info(file).unwrap();
+    assert_eq!(
+        "test d'écriture dans un fichier d'un dossier\n".as_bytes(),
+        data.as_slice()
+    );
+    assert_eq!(fs_lock.read_file_info(test_dir), Err(FsError::NotAFile));
+}
+
+#[test_case]
+fn short_reads_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    // Image tronquée au milieu du cluster de test.txt (cluster 11)
+    let cluster_sector = fs_lock.data_sector + (11 - 2) * fs_lock.sectors_per_cluster;
+    let cut = (cluster_sector * fs_lock.bytes_per_sector) as usize + 100;
+    let truncated = alloc::vec::Vec::from(&fs_lock.disk[..cut]).into_boxed_slice();
+    let expected = fs_lock.read_cluster(11);
+
+    let mut short_fs = Fat32FileSystem::new(truncated);
+    assert_eq!(
+        short_fs.try_read_sector(cluster_sector),
+        Err(FsError::OutOfBounds)
+    );
+
+    short_fs.options.allow_short_reads = true;
+    let data = short_fs.read_cluster(11);
+    assert_eq!(data.len(), expected.len());
+    assert_eq!(&data[..100], &expected[..100]);
+    assert!(data[100..].iter().all(|&b| b == 0));
+    assert_eq!(short_fs.read_file("/test.txt", None).unwrap().len(), 5);
+}
+
+#[test_case]
+fn readahead_test() {
+    // Volume de 8 clusters contenant un fichier de 4 clusters contigus (3 à 6)
+    let mut disk = alloc::vec![0u8; 10 * 512];
+
+    set_fat_entry(&mut disk, 2, 0x0FFFFFFF);
+    for cluster in 3..6 {
+        set_fat_entry(&mut disk, cluster, cluster + 1);
+    }
+    set_fat_entry(&mut disk, 6, 0x0FFFFFFF);
+
+    let root = 2 * 512;
+    disk[root..root + 11].copy_from_slice(b"BIG     BIN");
+    disk[root + 11] = 0x20;
+    disk[root + 26..root + 28].copy_from_slice(&3u16.to_le_bytes());
+    disk[root + 28..root + 32].copy_from_slice(&2048u32.to_le_bytes());
+    for (i, byte) in disk[3 * 512..7 * 512].iter_mut().enumerate() {
+        *byte = (i / 512) as u8 + 1;
+    }
+
+    let mut fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+    let device = DeviceHook::attach(&mut fs);
+    let file = list_directory_entries(&fs, fs.root_cluster).remove(0);
+
+    let before = device.reads();
+    let data = fs.read_file_info(&file).unwrap();
+    let reads_without = device.reads() - before;
+
+    fs.options.readahead_clusters = 4;
+    let before = device.reads();
+    let data_readahead = fs.read_file_info(&file).unwrap();
+    let reads_with = device.reads() - before;
+
+    assert_eq!(data.len(), 2048);
+    assert_eq!(data, data_readahead);
+    assert!(reads_with < reads_without);
+}
+
+#[test_case]
+fn extend_directory_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+
+    // Un cluster de 512 octets contient 16 entrées : la racine doit s’agrandir
+    for i in 0..20 {
+        shell
+            .touch("", &alloc::format!("F{}", i))
+            .expect("Erreur lors du touch");
+    }
+
+    let fs_lock = fs.lock();
+    assert_eq!(fs_lock.cluster_chain(fs_lock.root_cluster).len(), 2);
+
+    let entries = list_directory_entries(&fs_lock, fs_lock.root_cluster);
+    assert_eq!(entries.len(), 22);
+    for i in 0..20 {
+        let name = alloc::format!("F{}", i);
+        assert!(entries.iter().any(|e| e.name == name));
+    }
+}
+
+#[test_case]
+fn verify_file_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+
+    assert_eq!(fs_lock.verify_file("/test.txt"), Ok(()));
+
+    // test.txt (2e slot de la racine) n’occupe qu’un cluster mais déclare 2000 octets
+    let size_offset = (fs_lock.data_sector * fs_lock.bytes_per_sector) as usize + 32 + 28;
+    fs_lock.disk[size_offset..size_offset + 4].copy_from_slice(&2000u32.to_le_bytes());
+
+    assert_eq!(
+        fs_lock.verify_file("/test.txt"),
+        Err(FsError::SizeMismatch {
+            expected: 4,
+            actual: 1
+        })
+    );
+}
+
+#[derive(Debug)]
+struct FixedClock;
+
+impl FatClock for FixedClock {
+    fn now(&self) -> FatTimestamp {
+        FatTimestamp::new(2024, 5, 17, 13, 45, 30)
+    }
+}
+
+#[test_case]
+fn clock_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+
+    fs.lock().set_clock(Rc::new(FixedClock));
+    shell.touch("", "CLOCK_T").expect("Erreur lors du touch");
+
+    let entries = shell.ls_entries();
+    let file = entries.iter().find(|e| e.name == "CLOCK_T").unwrap();
+    assert_eq!(file.created, FatTimestamp::new(2024, 5, 17, 13, 45, 30));
+    assert_eq!(file.modified, file.created);
+
+    // Date FAT : (2024 - 1980) << 9 | 5 << 5 | 17
+    assert_eq!(file.created.to_fat_date(), (44 << 9) | (5 << 5) | 17);
+    assert_eq!(file.created.to_fat_time(), (13 << 11) | (45 << 5) | 15);
+}
+
+#[test_case]
+fn volume_label_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+
+    // Étiquette de volume écrite dans le premier slot libre de la racine
+    let slot = (fs_lock.data_sector * fs_lock.bytes_per_sector) as usize + 6 * 32;
+    fs_lock.disk[slot..slot + 11].copy_from_slice(b"MYVOLUME   ");
+    fs_lock.disk[slot + 11] = 0x08;
+
+    let files = list_directory_entries(&fs_lock, fs_lock.root_cluster);
+    assert!(!files.iter().any(|f| f.is_volume_label));
+    assert_eq!(
+        ["test.txt", "test_dir"],
+        list_files_names(&files).as_slice()
+    );
+
+    let raw = list_directory_entries_raw(&fs_lock, fs_lock.root_cluster);
+    let label = raw.iter().find(|f| f.is_volume_label).unwrap();
+    assert_eq!(label.name, "MYVOLUME");
+    assert!(!label.is_directory);
+}
+
+#[test_case]
+fn resolve_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let test_dir = fs_lock.resolve("/test_dir", None).unwrap();
+    assert_eq!(test_dir.parent_cluster, fs_lock.root_cluster);
+
+    let resolved = fs_lock.resolve("/test_dir/test_dir_file", None).unwrap();
+    assert_eq!(resolved.parent_cluster, test_dir.file.start_cluster);
+    assert_eq!(resolved.components, ["test_dir", "test_dir_file"]);
+    assert_eq!(resolved.file.name, "test_dir_file");
+
+    let back = fs_lock.resolve("test_dir/../test.txt", None).unwrap();
+    assert_eq!(back.components, ["test.txt"]);
+
+    assert_eq!(
+        fs_lock.resolve("/test.txt/file", None),
+        Err(FsError::NotADirectory)
+    );
+    assert_eq!(fs_lock.resolve("/missing", None), Err(FsError::NotFound));
+}
+
+#[test_case]
+fn fat_mirror_test() {
+    let (fs, hook) = init_fs_with_hook();
+    let shell = ShellSession::new(fs.clone());
+
+    assert_eq!(fs.lock().num_fats, 2);
+
+    shell.touch("", "MIRROR_T").expect("Erreur lors du touch");
+    shell
+        .write("MIRROR_T", "mirror")
+        .expect("erreur lors du write");
+
+    let file = shell
+        .ls_entries()
+        .into_iter()
+        .find(|e| e.name == "MIRROR_T")
+        .unwrap();
+    let fs_lock = fs.lock();
+
+    // La FAT miroir est tenue à jour et contient la même chaîne que la FAT principale
+    for cluster in [fs_lock.root_cluster, 11, file.start_cluster] {
+        assert_eq!(
+            fs_lock.read_fat_entry_from(0, cluster),
+            fs_lock.read_fat_entry_from(1, cluster)
+        );
+    }
+    assert_eq!(
+        fs_lock.read_fat_entry_from(1, file.start_cluster),
+        Ok(0x0FFFFFFF)
+    );
+    assert_eq!(
+        fs_lock.read_fat_entry_from(2, file.start_cluster),
+        Err(FsError::OutOfBounds)
+    );
+    drop(fs_lock);
+
+    // Secteur de la FAT principale illisible (et contenu faussé) : la copie miroir prend le relais
+    shell
+        .write("MIRROR_T", &"m".repeat(1500))
+        .expect("erreur lors du write");
+    let mut fs_lock = fs.lock();
+    let file = fs_lock.resolve("/MIRROR_T", None).unwrap().file;
+    let chain = fs_lock.cluster_chain(file.start_cluster);
+    assert_eq!(chain.len(), 3);
+
+    let entry_sector = fs_lock.fat_sector + file.start_cluster * 4 / fs_lock.bytes_per_sector;
+    let offset = (fs_lock.fat_sector * fs_lock.bytes_per_sector + file.start_cluster * 4) as usize;
+    fs_lock.disk[offset..offset + 4].copy_from_slice(&0x0FFF_FFFFu32.to_le_bytes());
+    hook.fail_sector(entry_sector);
+
+    assert_eq!(
+        fs_lock.read_fat_entry_from(0, file.start_cluster),
+        Err(FsError::BadSector)
+    );
+    assert_eq!(
+        fs_lock.read_fat_entry_from(1, file.start_cluster),
+        Ok(chain[1])
+    );
+    assert_eq!(fs_lock.cluster_chain(file.start_cluster), chain);
+    assert_eq!(fs_lock.checked_cluster_chain(file.start_cluster), Ok(chain));
+    assert_eq!(
+        fs_lock.read_file("/MIRROR_T", None).unwrap(),
+        "m".repeat(1500)
+    );
+}
+
+#[test_case]
+fn list_deleted_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+
+    shell.touch("", "DEL_T").expect("Erreur lors du touch");
+    shell
+        .write("DEL_T", "deleted")
+        .expect("erreur lors du write");
+    let file = shell
+        .ls_entries()
+        .into_iter()
+        .find(|e| e.name == "DEL_T")
+        .unwrap();
+
+    shell.rm("DEL_T").expect("Erreur lors du rm");
+    assert!(!shell.ls_entries().iter().any(|e| e.name == "DEL_T"));
+
+    let fs_lock = fs.lock();
+    let deleted = list_deleted(&fs_lock, fs_lock.root_cluster);
+    let entry = deleted.iter().find(|e| e.name == "?EL_T").unwrap();
+    assert_eq!(entry.start_cluster, file.start_cluster);
+    assert_eq!(entry.size, 7);
+    assert!(!entry.is_directory);
+}
+
+#[test_case]
+fn undelete_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+    // En first-fit, l’écriture de NEW_T réutilise le cluster libéré par UND_T
+    fs.lock().options.alloc_strategy = AllocStrategy::FirstFit;
+
+    shell.touch("", "UND_T").expect("Erreur lors du touch");
+    shell
+        .write("UND_T", "restored")
+        .expect("erreur lors du write");
+    shell.rm("UND_T").expect("Erreur lors du rm");
+
+    fs.lock()
+        .undelete("/", "?ND_T", "UND_T")
+        .expect("Erreur lors du undelete");
+
+    let content = fs.lock().read_file("/UND_T", None).unwrap();
+    assert_eq!(content, "restored");
+
+    shell.rm("UND_T").expect("Erreur lors du rm");
+    shell
+        .touch("test_dir", "NEW_T")
+        .expect("Erreur lors du touch");
+    shell
+        .write("/test_dir/NEW_T", "new")
+        .expect("erreur lors du write");
+    assert_eq!(
+        fs.lock().undelete("/", "?ND_T", "UND_T"),
+        Err(FsError::ClusterReallocated)
+    );
+}
+
+#[test_case]
+fn undelete_validation_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+    fs_lock.create_file("", "und_v").unwrap();
+    fs_lock.write_file("/UND_V", None, b"restore me").unwrap();
+    fs_lock.create_file("", "other").unwrap();
+    let cluster = fs_lock.resolve("/UND_V", None).unwrap().file.start_cluster;
+    let location = fs_lock.locate_entry("/UND_V", None).unwrap();
+    let sector = fs_lock.cluster_to_sector(location.cluster).unwrap();
+    let offset = (sector * fs_lock.bytes_per_sector) as usize + location.offset_in_cluster;
+    fs_lock.remove_file("/UND_V", None).unwrap();
+    let free = fs_lock.recompute_free_count();
+
+    // Cluster de départ hors du volume (mot haut 0x0FFF ou 0xFFFF), puis chaîne trop longue
+    for high in [0x0FFFu16, 0xFFFF] {
+        fs_lock.disk[offset + 20..offset + 22].copy_from_slice(&high.to_le_bytes());
+        assert_eq!(
+            fs_lock.undelete("/", "?ND_V", "UND_V"),
+            Err(FsError::InvalidCluster)
+        );
+    }
+    fs_lock.disk[offset + 20..offset + 22].fill(0);
+    fs_lock.disk[offset + 28..offset + 32].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(
+        fs_lock.undelete("/", "?ND_V", "UND_V"),
+        Err(FsError::InvalidCluster)
+    );
+    fs_lock.disk[offset + 28..offset + 32].copy_from_slice(&10u32.to_le_bytes());
+
+    // Une entrée vivante pointe déjà sur le cluster libéré : rien n’est écrit
+    let other = fs_lock.locate_entry("/OTHER", None).unwrap();
+    let other_sector = fs_lock.cluster_to_sector(other.cluster).unwrap();
+    let other_offset = (other_sector * fs_lock.bytes_per_sector) as usize + other.offset_in_cluster;
+    fs_lock.disk[other_offset + 26..other_offset + 28]
+        .copy_from_slice(&(cluster as u16).to_le_bytes());
+    assert_eq!(
+        fs_lock.undelete("/", "?ND_V", "UND_V"),
+        Err(FsError::ClusterReallocated)
+    );
+    assert_eq!(fs_lock.recompute_free_count(), free);
+    assert_eq!(fs_lock.disk[offset], 0xE5);
+
+    fs_lock.disk[other_offset + 26..other_offset + 28].fill(0);
+    fs_lock.undelete("/", "?ND_V", "UND_V").unwrap();
+    assert_eq!(fs_lock.read_file("/UND_V", None).unwrap(), "restore me");
+}
+
+#[test_case]
+fn strict_dot_entry_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    fs.lock().options.strict_dot_entries = true;
+
+    shell.cd("test_dir").expect("Erreur lors du cd");
+    shell.cd("..").expect("Erreur lors du cd");
+
+    shell.mkdir("", "BAD_D").expect("Erreur lors du mkdir");
+    let dir = shell
+        .ls_entries()
+        .into_iter()
+        .find(|e| e.name == "BAD_D")
+        .unwrap();
+
+    {
+        let mut fs_lock = fs.lock();
+        let offset = ((fs_lock.data_sector + (dir.start_cluster - 2) * fs_lock.sectors_per_cluster)
+            * fs_lock.bytes_per_sector) as usize;
+        let wrong = (dir.start_cluster + 1) as u16;
+        fs_lock.disk[offset + 26..offset + 28].copy_from_slice(&wrong.to_le_bytes());
+    }
+
+    assert_eq!(shell.cd("BAD_D"), Err(FsError::CorruptEntry));
+
+    fs.lock().options.strict_dot_entries = false;
+    shell.cd("BAD_D").expect("Erreur lors du cd");
+}
+
+#[test_case]
+fn read_sectors_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let sectors = fs_lock
+        .read_sectors(fs_lock.data_sector, fs_lock.sectors_per_cluster)
+        .unwrap();
+    assert_eq!(sectors, fs_lock.read_cluster(2));
+
+    let end = (fs_lock.disk.len() as u32) / fs_lock.bytes_per_sector;
+    assert_eq!(fs_lock.read_sectors(end - 1, 2), Err(FsError::OutOfBounds));
+}
+
+#[test_case]
+fn check_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    assert!(fs_lock.verify_fats().is_empty());
+    assert_eq!(
+        fs_lock.fsinfo_free_count(),
+        Some(fs_lock.recompute_free_count())
+    );
+
+    let report = fs_lock.check();
+    assert!(report.is_clean(), "{:?}", report.warnings);
+}
+
+#[test_case]
+fn check_after_write_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+
+    fs_lock.create_file("", "sync.txt").unwrap();
+    fs_lock.write_file("/SYNC.TXT", None, &[3u8; 1500]).unwrap();
+    fs_lock.mkdir("", "syncdir").unwrap();
+    let report = fs_lock.check();
+    assert!(report.is_clean(), "{:?}", report.warnings);
+
+    let file = fs_lock.resolve("/SYNC.TXT", None).unwrap().file;
+    let last = *fs_lock.cluster_chain(file.start_cluster).last().unwrap();
+    let next_free = fs_lock.fsinfo_next_free().unwrap();
+    assert!(next_free > last);
+
+    fs_lock.remove_file("/SYNC.TXT", None).unwrap();
+    assert_eq!(
+        fs_lock.fsinfo_free_count(),
+        Some(fs_lock.recompute_free_count())
+    );
+    let report = fs_lock.check();
+    assert!(report.is_clean(), "{:?}", report.warnings);
+}
+
+#[test_case]
+fn ls_columns_test() {
+    let names: alloc::vec::Vec<alloc::string::String> = ["a", "bb", "ccc", "dddd", "e/"]
+        .iter()
+        .map(|n| n.to_string())
+        .collect();
+
+    assert_eq!(format_columns(&names, 14), "a     dddd\nbb    e/\nccc\n");
+    assert_eq!(format_columns(&names, 80), "a     bb    ccc   dddd  e/\n");
+    assert_eq!(format_columns(&names, 1), "a\nbb\nccc\ndddd\ne/\n");
+}
+
+#[test_case]
+fn bad_geometry_test() {
+    let mut disk = alloc::vec::Vec::from(DISK_IMAGE).into_boxed_slice();
+    disk[11..13].copy_from_slice(&510u16.to_le_bytes());
+    assert_eq!(
+        Fat32FileSystem::try_new(disk).err(),
+        Some(FsError::BadGeometry)
+    );
+
+    let disk = alloc::vec![0u8; 510 * 8].into_boxed_slice();
+    let fs = Fat32FileSystem::new_from_parts(disk, 510, 1, 1, 4, 2);
+    assert_eq!(fs.read_fat_entry_from(0, 127), Err(FsError::BadGeometry));
+}
+
+#[test_case]
+fn cd_back_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+
+    shell
+        .mkdir("test_dir", "SUB")
+        .expect("Erreur lors du mkdir");
+
+    shell.cd("test_dir").expect("Erreur lors du cd");
+    let test_dir_cluster = shell.current_cluster;
+    shell.cd("SUB").expect("Erreur lors du cd");
+    assert_eq!(shell.current_path, "/test_dir/SUB");
+
+    shell.cd_back().expect("Erreur lors du cd_back");
+    assert_eq!(shell.current_cluster, test_dir_cluster);
+    assert_eq!(shell.current_path, "/test_dir");
+
+    shell.cd_back().expect("Erreur lors du cd_back");
+    assert_eq!(shell.current_path, "/");
+    assert_eq!(shell.cd_back(), Err(FsError::NotFound));
+}
+
+#[test_case]
+fn full_directory_cluster_test() {
+    // Racine d’un seul cluster entièrement occupée, suivie d’un cluster hors chaîne
+    let mut disk = alloc::vec![0u8; 6 * 512];
+    set_fat_entry(&mut disk, 2, 0x0FFFFFFF);
+
+    let root = 2 * 512;
+    for i in 0..16 {
+        let entry = root + i * 32;
+        let name = alloc::format!("F{:<7}   ", i);
+        disk[entry..entry + 11].copy_from_slice(name.as_bytes());
+        disk[entry + 11] = 0x20;
+    }
+
+    let ghost = 3 * 512;
+    disk[ghost..ghost + 11].copy_from_slice(b"GHOST      ");
+    disk[ghost + 11] = 0x20;
+    disk[ghost + 32] = 0xE5;
+
+    let fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+
+    let files = list_directory_entries(&fs, fs.root_cluster);
+    assert_eq!(files.len(), 16);
+    assert_eq!(files[15].name, "F15");
+    assert!(!files.iter().any(|f| f.name == "GHOST"));
+    assert!(list_deleted(&fs, fs.root_cluster).is_empty());
+}
+
+#[test_case]
+fn extension_stem_test() {
+    let file = |name: &str| FileInfo::new(name.to_string(), false, 0, 0);
+
+    assert_eq!(file("file.txt").stem(), "file");
+    assert_eq!(file("file.txt").extension(), Some("txt"));
+
+    assert_eq!(file(".hidden").stem(), ".hidden");
+    assert_eq!(file(".hidden").extension(), None);
+
+    assert_eq!(file("archive.tar.gz").stem(), "archive.tar");
+    assert_eq!(file("archive.tar.gz").extension(), Some("gz"));
+
+    assert_eq!(file("noext").stem(), "noext");
+    assert_eq!(file("noext").extension(), None);
+
+    assert_eq!(file("trailing.").stem(), "trailing");
+    assert_eq!(file("trailing.").extension(), Some(""));
+}
+
+#[test_case]
+fn checksum_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+    let before = fs.lock().checksum();
+
+    shell.ls(None).unwrap();
+    shell.ls(Some("test_dir")).unwrap();
+    shell.cat("test.txt").unwrap();
+    assert_eq!(fs.lock().checksum(), before);
+
+    shell.touch("", "SUM_T").expect("Erreur lors du touch");
+    assert_ne!(fs.lock().checksum(), before);
+}
+
+#[test_case]
+fn resolve_verbose_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+    shell
+        .mkdir("test_dir", "SUB")
+        .expect("Erreur lors du mkdir");
+
+    let fs_lock = fs.lock();
+    let (error, resolved) = fs_lock
+        .resolve_verbose("/test_dir/SUB/missing/file", None)
+        .unwrap_err();
+    assert_eq!(error, FsError::NotFound);
+    assert_eq!(resolved, ["test_dir", "SUB"]);
+
+    let (error, resolved) = fs_lock
+        .resolve_verbose("/test.txt/inner", None)
+        .unwrap_err();
+    assert_eq!(error, FsError::NotADirectory);
+    assert_eq!(resolved, ["test.txt"]);
+
+    let file = fs_lock.resolve_verbose("/test_dir/SUB", None).unwrap();
+    assert!(file.is_directory);
+}
+
+#[test_case]
+fn ls_entries_where_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+
+    let dirs = shell.ls_entries_where(|f| f.is_directory);
+    assert_eq!(["test_dir"], list_files_names(&dirs).as_slice());
+
+    let files = shell.ls_entries_where(|f| !f.is_directory);
+    assert_eq!(["test.txt"], list_files_names(&files).as_slice());
+
+    let txt = shell.ls_entries_where(|f| f.extension() == Some("txt"));
+    assert_eq!(txt.len(), 1);
+}
+
+#[test_case]
+fn lfn_surrogate_fallback_test() {
+    let mut disk = alloc::vec![0u8; 6 * 512];
+    set_fat_entry(&mut disk, 2, 0x0FFFFFFF);
+
+    let short_name = b"FILE    TXT";
+    let checksum = short_name.iter().fold(0u8, |sum, &c| {
+        ((sum & 1) << 7).wrapping_add(sum >> 1).wrapping_add(c)
+    });
+
+    // Entrée LFN unique contenant un surrogate isolé (0xD800)
+    let lfn = 2 * 512;
+    let chars: [u16; 13] = [
+        0x0061, 0xD800, 0x0062, 0x0000, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF,
+        0xFFFF, 0xFFFF,
+    ];
+    let offsets = [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+    disk[lfn] = 0x41;
+    disk[lfn + 11] = 0x0F;
+    disk[lfn + 13] = checksum;
+    for (ch, off) in chars.iter().zip(offsets) {
+        disk[lfn + off..lfn + off + 2].copy_from_slice(&ch.to_le_bytes());
+    }
+
+    let sfn = lfn + 32;
+    disk[sfn..sfn + 11].copy_from_slice(short_name);
+    disk[sfn + 11] = 0x20;
+
+    let fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+
+    let files = list_directory_entries(&fs, fs.root_cluster);
+    assert_eq!(["FILE.TXT"], list_files_names(&files).as_slice());
+    assert!(files[0].lfn_fallback);
+}
+
+#[test_case]
+fn clamp_size_to_chain_test() {
+    // STALE.TXT déclare 2000 octets pour une chaîne d’un seul cluster,
+    // SHORT.TXT déclare 5 octets pour une chaîne de deux clusters
+    let mut disk = alloc::vec![0u8; 8 * 512];
+    set_fat_entry(&mut disk, 2, 0x0FFFFFFF);
+    set_fat_entry(&mut disk, 3, 0x0FFFFFFF);
+    set_fat_entry(&mut disk, 4, 5);
+    set_fat_entry(&mut disk, 5, 0x0FFFFFFF);
+
+    let root = 2 * 512;
+    disk[root..root + 11].copy_from_slice(b"STALE   TXT");
+    disk[root + 11] = 0x20;
+    disk[root + 26..root + 28].copy_from_slice(&3u16.to_le_bytes());
+    disk[root + 28..root + 32].copy_from_slice(&2000u32.to_le_bytes());
+    disk[root + 32..root + 43].copy_from_slice(b"SHORT   TXT");
+    disk[root + 43] = 0x20;
+    disk[root + 58..root + 60].copy_from_slice(&4u16.to_le_bytes());
+    disk[root + 60..root + 64].copy_from_slice(&5u32.to_le_bytes());
+    disk[4 * 512..4 * 512 + 5].copy_from_slice(b"short");
+
+    let mut fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+    let device = DeviceHook::attach(&mut fs);
+    let stale = fs.resolve("/STALE.TXT", None).unwrap().file;
+    let short = fs.resolve("/SHORT.TXT", None).unwrap().file;
+
+    assert_eq!(fs.read_file_info(&stale).unwrap().len(), 512);
+    let before = device.reads();
+    assert_eq!(fs.read_file_info(&short).unwrap(), b"short");
+    let unclamped_reads = device.reads() - before;
+
+    fs.options.clamp_size_to_chain = true;
+    assert_eq!(fs.read_file_info(&stale).unwrap().len(), 512);
+
+    // Le second cluster de SHORT.TXT n’est plus lu
+    let before = device.reads();
+    assert_eq!(fs.read_file_info(&short).unwrap(), b"short");
+    assert_eq!(device.reads() - before, unclamped_reads - 1);
+}
+
+#[test_case]
+fn fat_dir_to_bytes_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let root = fs_lock.read_cluster(fs_lock.root_cluster);
+    let entry = FatDir::new(&root[32..64]);
+    assert_eq!(&entry.to_bytes()[..], &root[32..64]);
+
+    let mut crafted = entry;
+    crafted.name = *b"ROUND   TRP";
+    crafted.size = 0x1234;
+    crafted.set_first_cluster(0x0012_3456);
+    assert_eq!(crafted.first_cluster_high, 0x0012);
+    assert_eq!(crafted.first_cluster_low, 0x3456);
+    assert_eq!(crafted.first_cluster(), 0x0012_3456);
+
+    assert_eq!(FatDir::new(&crafted.to_bytes()), crafted);
+}
+
+#[test_case]
+fn lfn_to_bytes_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let root = fs_lock.read_cluster(fs_lock.root_cluster);
+    let lfn = LongFileName::new(&root[0..32]);
+    assert_eq!(lfn.attr, 0x0F);
+    assert_eq!(&lfn.to_bytes()[..], &root[0..32]);
+
+    let crafted = LongFileName {
+        seq_num: 0x42,
+        name_1: *b"a\0b\0c\0d\0e\0",
+        attr: 0x0F,
+        l_type: 0,
+        chksum: 0xA5,
+        name_2: *b"f\0g\0h\0i\0j\0k\0",
+        reserved_fch: 0,
+        name_3: [0x00, 0x00, 0xFF, 0xFF],
+    };
+    let parsed = LongFileName::new(&crafted.to_bytes());
+    assert_eq!(parsed, crafted);
+    assert_eq!(parsed.seq_num, 0x42);
+    assert_eq!(parsed.chksum, 0xA5);
+}
+
+#[test_case]
+fn append_contiguous_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+
+    shell.write("HOLE_T", "hole").expect("erreur lors du write");
+    shell.touch("", "APP_T").expect("Erreur lors du touch");
+    shell.write("APP_T", "start").expect("erreur lors du write");
+
+    // Le trou laissé par HOLE_T précède APP_T : l’ajout ne doit pas le combler
+    let mut fs_lock = fs.lock();
+    let hole = fs_lock.resolve("/HOLE_T", None).unwrap().file.start_cluster;
+    fs_lock.remove_file("/HOLE_T", None).unwrap();
+
+    let tail = [b'x'; 1000];
+    fs_lock.append_file("/APP_T", &tail).unwrap();
+
+    let file = fs_lock.resolve("/APP_T", None).unwrap().file;
+    assert_eq!(file.size, 1005);
+
+    let chain = fs_lock.cluster_chain(file.start_cluster);
+    assert_eq!(chain.len(), 2);
+    assert_eq!(chain[1], chain[0] + 1);
+    assert!(hole < chain[0]);
+    assert!(!chain.contains(&hole));
+
+    let content = fs_lock.read_file_info(&file).unwrap();
+    assert_eq!(&content[..5], b"start");
+    assert!(content[5..].iter().all(|&b| b == b'x'));
+}
+
+#[test_case]
+fn append_size_limit_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+    fs_lock.create_file("", "limit.txt").unwrap();
+    fs_lock.write_file("/LIMIT.TXT", None, b"limit").unwrap();
+
+    // Taille déclarée proche de la limite FAT32
+    let location = fs_lock.locate_entry("/LIMIT.TXT", None).unwrap();
+    let sector = fs_lock.cluster_to_sector(location.cluster).unwrap();
+    let offset = (sector * fs_lock.bytes_per_sector) as usize + location.offset_in_cluster + 28;
+    fs_lock.disk[offset..offset + 4].copy_from_slice(&(u32::MAX - 10).to_le_bytes());
+
+    let free = fs_lock.recompute_free_count();
+    assert_eq!(
+        fs_lock.append_file("/LIMIT.TXT", &[1u8; 20]),
+        Err(FsError::FileTooLarge)
+    );
+    assert_eq!(fs_lock.recompute_free_count(), free);
+    assert_eq!(
+        fs_lock.resolve("/LIMIT.TXT", None).unwrap().file.size,
+        u32::MAX - 10
+    );
+}
+
+#[test_case]
+fn cluster_to_sector_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    assert_eq!(fs_lock.cluster_to_sector(2), Ok(fs_lock.data_sector));
+    assert_eq!(
+        fs_lock.cluster_to_sector(3),
+        Ok(fs_lock.data_sector + fs_lock.sectors_per_cluster)
+    );
+    assert_eq!(fs_lock.cluster_to_sector(1), Err(FsError::InvalidCluster));
+    assert_eq!(
+        fs_lock.cluster_to_sector(u32::MAX),
+        Err(FsError::InvalidCluster)
+    );
+}
+
+#[derive(Debug)]
+struct AtClock(FatTimestamp);
+
+impl FatClock for AtClock {
+    fn now(&self) -> FatTimestamp {
+        self.0
+    }
+}
+
+#[test_case]
+fn write_updates_mtime_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+
+    let created = FatTimestamp::new(2024, 1, 2, 3, 4, 6);
+    let written = FatTimestamp::new(2025, 7, 8, 9, 10, 12);
+    let appended = FatTimestamp::new(2026, 3, 4, 5, 6, 8);
+
+    fs.lock().set_clock(Rc::new(AtClock(created)));
+    shell.touch("", "MTIME_T").expect("Erreur lors du touch");
+
+    fs.lock().set_clock(Rc::new(AtClock(written)));
+    shell
+        .write("MTIME_T", "content")
+        .expect("erreur lors du write");
+
+    let file = fs.lock().resolve("/MTIME_T", None).unwrap().file;
+    assert_eq!(file.created, created);
+    assert_eq!(file.modified, written);
+
+    fs.lock().set_clock(Rc::new(AtClock(appended)));
+    fs.lock().append_file("/MTIME_T", b" more").unwrap();
+
+    let file = fs.lock().resolve("/MTIME_T", None).unwrap().file;
+    assert_eq!(file.created, created);
+    assert_eq!(file.modified, appended);
+}
+
+#[test_case]
+fn run_command_interactive_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    shell.start_capture();
+
+    shell.run_command_interactive("cd nonexistent");
+    assert_eq!(shell.take_output(), "cd: File not found\n");
+    assert_eq!(shell.current_path, "/");
+
+    shell.run_command_interactive("frobnicate");
+    assert_eq!(shell.take_output(), "frobnicate: unknown command\n");
+
+    shell.run_command_interactive("cd test_dir");
+    shell.run_command_interactive("touch CMD_T");
+    shell.run_command_interactive("write CMD_T hello shell");
+    shell.run_command_interactive("cat CMD_T");
+    assert_eq!(shell.take_output(), "hello shell\n");
+}
+
+#[test_case]
+fn boot_backup_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+
+    assert_eq!(fs_lock.backup_boot_sector, 6);
+    assert_eq!(fs_lock.verify_boot_backup(), Ok(()));
+
+    // Octet « secteurs par cluster » (offset 13) de la copie de secours
+    let backup_spc_offset = 6 * 512 + 13;
+    fs_lock.disk[backup_spc_offset] ^= 0xFF;
+    assert_eq!(fs_lock.verify_boot_backup(), Err(FsError::BootMismatch));
+}
+
+#[test_case]
+fn read_text_prefix_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+
+    shell.touch("", "BIN_T").expect("Erreur lors du touch");
+    fs.lock()
+        .append_file("/BIN_T", b"header\xFFbinary")
+        .unwrap();
+
+    let fs_lock = fs.lock();
+    assert_eq!(fs_lock.read_file("/BIN_T", None), Err(FsError::InvalidUtf8));
+    assert_eq!(
+        fs_lock.read_text_prefix("/BIN_T", 64),
+        Ok("header".to_string())
+    );
+    assert_eq!(fs_lock.read_text_prefix("/BIN_T", 3), Ok("hea".to_string()));
+    assert_eq!(
+        fs_lock.read_text_prefix("/test_dir", 3),
+        Err(FsError::NotAFile)
+    );
+    drop(fs_lock);
+
+    // Chaîne corrompue après le deuxième cluster : seuls les clusters utiles sont suivis
+    let mut fs_lock = fs.lock();
+    fs_lock.write_file("/LONG_T", None, &[b'a'; 1500]).unwrap();
+    let chain = {
+        let file = fs_lock.resolve("/LONG_T", None).unwrap().file;
+        fs_lock.cluster_chain(file.start_cluster)
+    };
+    let offset = (fs_lock.fat_sector * fs_lock.bytes_per_sector + chain[1] * 4) as usize;
+    fs_lock.disk[offset..offset + 4].copy_from_slice(&1u32.to_le_bytes());
+
+    assert_eq!(
+        fs_lock.read_text_prefix("/LONG_T", 1024).unwrap().len(),
+        1024
+    );
+    assert_eq!(
+        fs_lock.read_text_prefix("/LONG_T", 1500),
+        Err(FsError::InvalidCluster)
+    );
+
+    fs_lock.options.max_read_bytes = 100;
+    assert_eq!(
+        fs_lock.read_text_prefix("/LONG_T", 101),
+        Err(FsError::FileTooLarge)
+    );
+    assert_eq!(fs_lock.read_text_prefix("/LONG_T", 100).unwrap().len(), 100);
+}
+
+#[test_case]
+fn root_entries_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let manual = list_directory_entries(&fs_lock, fs_lock.root_cluster);
+    assert_eq!(fs_lock.root_entries(), manual);
+
+    let root = fs_lock.root_info();
+    assert_eq!(root.name, "/");
+    assert!(root.is_directory);
+    assert_eq!(root.start_cluster, fs_lock.root_cluster);
+    assert_eq!(fs_lock.list_dir_info(&root), manual);
+}
+
+#[test_case]
+fn mkdir_rollback_test() {
+    // Racine pleine, un seul cluster libre (3) : l’agrandissement de la racine échoue
+    let mut disk = alloc::vec![0u8; 6 * 512];
+    set_fat_entry(&mut disk, 2, 0x0FFFFFFF);
+    set_fat_entry(&mut disk, 4, 0x0FFFFFFF);
+    set_fat_entry(&mut disk, 5, 0x0FFFFFFF);
+
+    let root = 2 * 512;
+    for i in 0..16 {
+        let entry = root + i * 32;
+        let name = alloc::format!("F{:<7}   ", i);
+        disk[entry..entry + 11].copy_from_slice(name.as_bytes());
+        disk[entry + 11] = 0x20;
+    }
+
+    let mut fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+    let free_before = fs.recompute_free_count();
+
+    assert_eq!(fs.mkdir("/", "NEWDIR"), Err(FsError::DiskFull.to_string()));
+    assert_eq!(fs.recompute_free_count(), free_before);
+    assert_eq!(fs.read_fat_entry_from(0, 3), Ok(0));
+    assert_eq!(fs.cluster_chain(fs.root_cluster), [2]);
+}
+
+#[test_case]
+fn cat_many_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    shell.start_capture();
+
+    shell.touch("", "CAT_A").expect("Erreur lors du touch");
+    shell.write("CAT_A", "first").expect("erreur lors du write");
+    shell.touch("", "CAT_B").expect("Erreur lors du touch");
+    shell
+        .write("CAT_B", "second")
+        .expect("erreur lors du write");
+
+    shell.cat_many(&["CAT_A", "CAT_B"]).unwrap();
+    assert_eq!(shell.take_output(), "first\nsecond\n");
+
+    assert_eq!(
+        shell.cat_many(&["CAT_A", "missing", "CAT_B"]),
+        Err(FsError::NotFound)
+    );
+    assert_eq!(
+        shell.take_output(),
+        "first\ncat: missing: File not found\nsecond\n"
+    );
+
+    shell.run_command_interactive("cat CAT_B CAT_A");
+    assert_eq!(shell.take_output(), "second\nfirst\n");
+}
+
+#[test_case]
+fn max_read_bytes_test() {
+    // HUGE.BIN déclare 0xFFFFFFFF octets sur une chaîne de trois clusters
+    let mut disk = alloc::vec![0u8; 8 * 512];
+    set_fat_entry(&mut disk, 2, 0x0FFFFFFF);
+    set_fat_entry(&mut disk, 3, 4);
+    set_fat_entry(&mut disk, 4, 5);
+    set_fat_entry(&mut disk, 5, 0x0FFFFFFF);
+
+    let root = 2 * 512;
+    disk[root..root + 11].copy_from_slice(b"HUGE    BIN");
+    disk[root + 11] = 0x20;
+    disk[root + 26..root + 28].copy_from_slice(&3u16.to_le_bytes());
+    disk[root + 28..root + 32].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let mut fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+    let huge = fs.resolve("/HUGE.BIN", None).unwrap().file;
+    assert_eq!(fs.read_file_info(&huge).unwrap().len(), 3 * 512);
+
+    fs.options.max_read_bytes = 1024;
+    assert_eq!(fs.read_file_info(&huge), Err(FsError::FileTooLarge));
+
+    // Seule la longueur réellement lue compte, même si la limite n’est pas un multiple
+    // de la taille de cluster : toute la chaîne sans bornage, la taille utile sinon
+    fs.options.max_read_bytes = 1500;
+    assert_eq!(fs.read_file_info(&huge), Err(FsError::FileTooLarge));
+    let small = FileInfo {
+        size: 1400,
+        ..huge.clone()
+    };
+    assert_eq!(fs.read_file_info(&small), Err(FsError::FileTooLarge));
+    let tiny = FileInfo {
+        size: 1,
+        ..huge.clone()
+    };
+    fs.options.max_read_bytes = 1024;
+    assert_eq!(fs.read_file_info(&tiny), Err(FsError::FileTooLarge));
+
+    fs.options.clamp_size_to_chain = true;
+    fs.options.max_read_bytes = 1500;
+    assert_eq!(fs.read_file_info(&huge), Err(FsError::FileTooLarge));
+    assert_eq!(fs.read_file_info(&small).unwrap().len(), 1400);
+    assert_eq!(fs.read_file_info(&tiny).unwrap().len(), 1);
+}
+
+#[test_case]
+fn mount_options_test() {
+    // L’entrée `.` de TEST_DIR est corrompue pour pointer sur un autre cluster
+    let mut lenient = Fat32FileSystem::new(alloc::vec::Vec::from(DISK_IMAGE).into_boxed_slice());
+    let dir = lenient.resolve("/test_dir", None).unwrap().file;
+    let offset =
+        (lenient.cluster_to_sector(dir.start_cluster).unwrap() * lenient.bytes_per_sector) as usize;
+    let wrong = (dir.start_cluster + 1) as u16;
+    lenient.disk[offset + 26..offset + 28].copy_from_slice(&wrong.to_le_bytes());
+
+    assert!(lenient.resolve("/test_dir/test_dir_file", None).is_ok());
+
+    let opts = MountOptions {
+        strict_dot_entries: true,
+        ..MountOptions::default()
+    };
+    // L’image corrompue est remontée sans copie supplémentaire (une seule image sur le tas)
+    let strict = Fat32FileSystem::new_with_options(lenient.disk, opts);
+    assert_eq!(strict.mount_options(), opts);
+    assert_eq!(
+        strict.resolve("/test_dir/test_dir_file", None).err(),
+        Some(FsError::CorruptEntry)
+    );
+}
+
+#[test_case]
+fn ls_file_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+
+    shell.start_capture();
+    assert_eq!(shell.ls(Some("test.txt")), Err(FsError::NotADirectory));
+    assert_eq!(shell.take_output(), "");
+
+    assert_eq!(shell.ls(Some("missing")), Err(FsError::NotFound));
+}
+
+#[test_case]
+fn import_tree_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+    let big = [7u8; 1300];
+
+    fs_lock
+        .import_tree(&[
+            ("/notes.txt", b"hello"),
+            ("docs/sub/big.bin", &big),
+            ("docs/readme", b"read me"),
+        ])
+        .unwrap();
+
+    assert_eq!(fs_lock.read_file("/NOTES.TXT", None).unwrap(), "hello");
+    assert_eq!(fs_lock.read_file("/DOCS/README", None).unwrap(), "read me");
+    let file = fs_lock.resolve("/DOCS/SUB/BIG.BIN", None).unwrap().file;
+    assert_eq!(fs_lock.read_file_info(&file).unwrap(), big);
+
+    // Les noms sont validés avant toute écriture
+    assert_eq!(
+        fs_lock.import_tree(&[("fresh.txt", b""), ("docs/not valid.txt", b"")]),
+        Err((1, FsError::InvalidName))
+    );
+    assert!(fs_lock.resolve("/FRESH.TXT", None).is_err());
+
+    assert_eq!(
+        fs_lock.import_tree(&[("notes.txt/x", b"")]),
+        Err((0, FsError::NotADirectory))
+    );
+    assert_eq!(
+        fs_lock.import_tree(&[("docs/readme", b"")]),
+        Err((0, FsError::AlreadyExists))
+    );
+
+    // Un fichier trop grand pour le volume ne laisse pas d’entrée tronquée
+    let mut small = blank_volume(1 << 20);
+    let huge = alloc::vec![1u8; 2 << 20];
+    assert_eq!(
+        small.import_tree(&[("keep.txt", b"kept"), ("out/huge.bin", &huge)]),
+        Err((1, FsError::DiskFull))
+    );
+    assert_eq!(small.read_file("/KEEP.TXT", None).unwrap(), "kept");
+    assert_eq!(
+        small.resolve("/OUT/HUGE.BIN", None).err(),
+        Some(FsError::NotFound)
+    );
+    assert!(small.resolve("/OUT", None).unwrap().file.is_directory);
+    assert!(small.check().is_clean());
+}
+
+#[test_case]
+fn export_tree_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let tree = fs_lock.export_tree();
+    let expected = "test d'écriture dans un fichier d'un dossier\n";
+
+    assert!(
+        tree.iter()
+            .any(|(path, data)| path == "/test_dir/" && data.is_empty())
+    );
+    assert!(
+        tree.iter()
+            .any(|(path, data)| path == "/test_dir/test_dir_file" && *data == expected.as_bytes())
+    );
+}
+
+#[test_case]
+fn compact_directory_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+    let dir = fs_lock
+        .resolve("/test_dir", None)
+        .unwrap()
+        .file
+        .start_cluster;
+
+    for i in 0..40 {
+        fs_lock
+            .create_file("test_dir", &alloc::format!("F{}.TXT", i))
+            .unwrap();
+    }
+    let grown = fs_lock.cluster_chain(dir).len();
+    assert!(grown > 1);
+
+    for i in 0..40 {
+        fs_lock
+            .remove_file(&alloc::format!("/test_dir/F{}.TXT", i), None)
+            .unwrap();
+    }
+
+    assert_eq!(fs_lock.compact_directory(dir), Ok(grown as u32 - 1));
+    assert_eq!(fs_lock.cluster_chain(dir).len(), 1);
+
+    let entries = list_directory_entries(&fs_lock, dir);
+    assert_eq!(entries[0].name, ".");
+    assert_eq!(entries[1].name, "..");
+    assert_eq!(
+        fs_lock.read_file("/test_dir/test_dir_file", None).unwrap(),
+        "test d'écriture dans un fichier d'un dossier\n"
+    );
+}
+
+#[test_case]
+fn read_cluster_into_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+    let cluster_bytes = (fs_lock.sectors_per_cluster * fs_lock.bytes_per_sector) as usize;
+
+    let mut buf = alloc::vec![0xAAu8; cluster_bytes + 4];
+    fs_lock.read_cluster_into(2, &mut buf).unwrap();
+    assert_eq!(buf[..cluster_bytes], fs_lock.read_cluster(2)[..]);
+    assert_eq!(buf[cluster_bytes..], [0xAA; 4]);
+
+    let mut small = alloc::vec![0u8; cluster_bytes - 1];
+    assert_eq!(
+        fs_lock.read_cluster_into(2, &mut small),
+        Err(FsError::OutOfBounds)
+    );
+}
+
+#[test_case]
+fn dot_entry_flag_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+    let dir = fs_lock
+        .resolve("/test_dir", None)
+        .unwrap()
+        .file
+        .start_cluster;
+
+    let entries = list_directory_entries(&fs_lock, dir);
+    let dots: alloc::vec::Vec<&str> = entries
+        .iter()
+        .filter(|f| f.is_dot_entry)
+        .map(|f| f.name.as_str())
+        .collect();
+    assert_eq!(dots, [".", ".."]);
+
+    // Fichier dont le nom long est littéralement « . »
+    let mut disk = alloc::vec![0u8; 6 * 512];
+    set_fat_entry(&mut disk, 2, 0x0FFFFFFF);
+
+    let short_name = b"DOT     TXT";
+    let checksum = short_name.iter().fold(0u8, |sum, &c| {
+        ((sum & 1) << 7).wrapping_add(sum >> 1).wrapping_add(c)
+    });
+
+    let lfn = 2 * 512;
+    disk[lfn] = 0x41;
+    disk[lfn + 1..lfn + 3].copy_from_slice(&0x002Eu16.to_le_bytes());
+    disk[lfn + 5..lfn + 11].fill(0xFF);
+    disk[lfn + 11] = 0x0F;
+    disk[lfn + 13] = checksum;
+    disk[lfn + 14..lfn + 26].fill(0xFF);
+    disk[lfn + 28..lfn + 32].fill(0xFF);
+
+    let sfn = lfn + 32;
+    disk[sfn..sfn + 11].copy_from_slice(short_name);
+    disk[sfn + 11] = 0x20;
+
+    let fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+    let files = list_directory_entries(&fs, fs.root_cluster);
+    assert_eq!(files[0].name, ".");
+    assert!(!files[0].is_dot_entry);
+}
+
+#[test_case]
+fn size_bytes_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let total = fs_lock.total_size_bytes();
+    assert_eq!(total, 64496 * 512);
+    assert_eq!(fs_lock.used_size_bytes() + fs_lock.free_size_bytes(), total);
+    assert_eq!(fs_lock.used_size_bytes(), 4 * 512);
+}
+
+#[test_case]
+fn file_clusters_test() {
+    let (fs, hook) = init_fs_with_hook();
+    let mut fs_lock = fs.lock();
+    let data = [3u8; 1300];
+    fs_lock.import_tree(&[("big.bin", &data)]).unwrap();
+
+    let lengths: alloc::vec::Vec<usize> = fs_lock
+        .file_clusters("/BIG.BIN")
+        .unwrap()
+        .map(|cluster| cluster.unwrap().len())
+        .collect();
+    assert_eq!(lengths, [512, 512, 276]);
+    assert_eq!(lengths.iter().sum::<usize>(), data.len());
+
+    let size = fs_lock.resolve("/test.txt", None).unwrap().file.size as usize;
+    let total: usize = fs_lock
+        .file_clusters("/test.txt")
+        .unwrap()
+        .map(|cluster| cluster.unwrap().len())
+        .sum();
+    assert_eq!(total, size);
+
+    assert_eq!(
+        fs_lock.file_clusters("/test_dir").err(),
+        Some(FsError::NotAFile)
+    );
+
+    // Un secteur illisible est signalé, puis l’itération s’arrête
+    let start = fs_lock
+        .resolve("/BIG.BIN", None)
+        .unwrap()
+        .file
+        .start_cluster;
+    let second = fs_lock.cluster_chain(start)[1];
+    let sector = fs_lock.cluster_to_sector(second).unwrap();
+    hook.fail_sector(sector);
+
+    let mut clusters = fs_lock.file_clusters("/BIG.BIN").unwrap();
+    assert_eq!(clusters.next().map(|c| c.map(|d| d.len())), Some(Ok(512)));
+    assert_eq!(clusters.next(), Some(Err(FsError::BadSector)));
+    assert_eq!(clusters.next(), None);
+    drop(fs_lock);
+
+    let shell = ShellSession::new(fs.clone());
+    shell.start_capture();
+    assert_eq!(shell.xxd("/BIG.BIN", 2000), Err(FsError::BadSector));
+    assert_eq!(shell.take_output().lines().count(), 32);
+    assert_eq!(shell.xxd("/BIG.BIN", 16), Ok(()));
+    assert_eq!(shell.more("/BIG.BIN", 10), Err(FsError::BadSector));
+}
+
+#[test_case]
+fn attribute_precedence_test() {
+    let mut disk = alloc::vec![0u8; 6 * 512];
+    set_fat_entry(&mut disk, 2, 0x0FFFFFFF);
+    set_fat_entry(&mut disk, 3, 0x0FFFFFFF);
+
+    // Étiquette et répertoire à la fois, puis répertoire archivé, puis fichier
+    let entries: [(&[u8; 11], u8); 3] = [
+        (b"LABELDIR   ", 0x18),
+        (b"SUBDIR     ", 0x30),
+        (b"FILE    TXT", 0x20),
+    ];
+    for (i, (name, attr)) in entries.iter().enumerate() {
+        let offset = 2 * 512 + i * 32;
+        disk[offset..offset + 11].copy_from_slice(*name);
+        disk[offset + 11] = *attr;
+        disk[offset + 26..offset + 28].copy_from_slice(&3u16.to_le_bytes());
+    }
+
+    let fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+
+    let files = list_directory_entries(&fs, fs.root_cluster);
+    assert_eq!(["SUBDIR", "FILE.TXT"], list_files_names(&files).as_slice());
+    assert!(files[0].is_directory);
+    assert!(!files[1].is_directory);
+
+    let raw = list_directory_entries_raw(&fs, fs.root_cluster);
+    assert!(raw[0].is_volume_label);
+    assert!(!raw[0].is_directory);
+}
+
+#[test_case]
+fn more_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    fs.lock()
+        .import_tree(&[("lines.txt", b"one\ntwo\nthree\nfour\nfive")])
+        .unwrap();
+
+    shell.start_capture();
+    shell.more("LINES.TXT", 3).unwrap();
+    assert_eq!(shell.take_output(), "one\ntwo\nthree\n-- truncated --\n");
+
+    shell.more("LINES.TXT", 5).unwrap();
+    assert_eq!(shell.take_output(), "one\ntwo\nthree\nfour\nfive\n");
+
+    shell.run_command("more test_dir/test_dir_file").unwrap();
+    assert_eq!(
+        shell.take_output(),
+        "test d'écriture dans un fichier d'un dossier\n"
+    );
+}
+
+#[test_case]
+fn lscluster_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    let root = fs.lock().root_cluster;
+
+    shell.start_capture();
+    shell.lscluster(root).unwrap();
+    let output = shell.take_output();
+
+    for entry in shell.ls_entries() {
+        let kind = if entry.is_directory {
+            "[DIR]"
+        } else {
+            "[FILE]"
+        };
+        assert!(output.contains(&alloc::format!("{} {}\n", kind, entry.name)));
+    }
+    assert!(output.contains("[DELETED] ?EST~1.TXT\n"));
+
+    assert_eq!(shell.lscluster(1), Err(FsError::InvalidCluster));
+    assert_eq!(
+        shell.run_command("lscluster abc"),
+        Err("invalid cluster".to_string())
+    );
+}
+
+#[test_case]
+fn is_valid_cluster_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+    let max = fs_lock.cluster_count() + 1;
+
+    assert!(!fs_lock.is_valid_cluster(0));
+    assert!(!fs_lock.is_valid_cluster(1));
+    assert!(fs_lock.is_valid_cluster(2));
+    assert!(fs_lock.is_valid_cluster(max));
+    assert!(!fs_lock.is_valid_cluster(max + 1));
+
+    let mut buf = alloc::vec![0u8; 512];
+    assert_eq!(fs_lock.read_cluster_into(max, &mut buf), Ok(()));
+    assert_eq!(
+        fs_lock.read_cluster_into(max + 1, &mut buf),
+        Err(FsError::InvalidCluster)
+    );
+
+    // Fichier non vide dont le cluster de départ sort du volume
+    let mut file = fs_lock.resolve("/test.txt", None).unwrap().file;
+    file.start_cluster = max + 1;
+    assert_eq!(fs_lock.read_file_info(&file), Err(FsError::InvalidCluster));
+}
+
+#[test_case]
+fn blank_volume_test() {
+    let mut fs = blank_volume(1 << 20);
+    assert!(fs.check().is_clean());
+    assert_eq!(fs.verify_boot_backup(), Ok(()));
+    assert!(list_directory_entries(&fs, fs.root_cluster).is_empty());
+
+    fs.create_file("", "hello.txt").unwrap();
+    fs.write_file("/HELLO.TXT", None, b"hello blank volume")
+        .unwrap();
+    assert_eq!(
+        fs.read_file("/HELLO.TXT", None).unwrap(),
+        "hello blank volume"
+    );
+
+    // Les écritures sont relues après démontage puis remontage du périphérique
+    let hello = fs.resolve("/HELLO.TXT", None).unwrap().file;
+    let sector = fs.cluster_to_sector(hello.start_cluster).unwrap();
+    let device = MemBlockDevice::unmount(fs);
+    assert_eq!(&device.read_block(sector)[..18], b"hello blank volume");
+
+    let mut fs = device.mount();
+    assert_eq!(
+        fs.read_file("/HELLO.TXT", None).unwrap(),
+        "hello blank volume"
+    );
+
+    fs.remove_file("/HELLO.TXT", None).unwrap();
+    assert!(fs.resolve("/HELLO.TXT", None).is_err());
+    assert_eq!(fs.recompute_free_count(), fs.cluster_count() - 1);
+}
+
+#[test_case]
+fn fat_sector_cache_test() {
+    let mut fs = blank_volume(1 << 20);
+    let device = DeviceHook::attach(&mut fs);
+    let data = alloc::vec![1u8; 200 * 512];
+    fs.import_tree(&[("big.bin", &data)]).unwrap();
+    let file = fs.resolve("/BIG.BIN", None).unwrap().file;
+
+    // 200 clusters contigus : leurs entrées tiennent dans deux ou trois secteurs de FAT
+    let before = device.reads();
+    let chain = fs.cluster_chain(file.start_cluster);
+    let reads = device.reads() - before;
+
+    assert_eq!(chain.len(), 200);
+    assert!(
+        reads <= 3,
+        "{} lectures pour {} clusters",
+        reads,
+        chain.len()
+    );
+}
+
+#[test_case]
+fn prompt_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    assert_eq!(shell.prompt(), "fat32:/> ");
+
+    shell.cd("test_dir").unwrap();
+    assert_eq!(shell.prompt(), "fat32:/test_dir> ");
+}
+
+#[test_case]
+fn rename_preserving_clusters_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+    let data = [9u8; 700];
+    fs_lock.import_tree(&[("a.txt", &data)]).unwrap();
+    let before = fs_lock.resolve("/A.TXT", None).unwrap().file;
+
+    fs_lock
+        .rename_preserving_clusters("/A.TXT", "a_much_longer_name.txt")
+        .unwrap();
+
+    assert!(fs_lock.resolve("/A.TXT", None).is_err());
+    let after = fs_lock
+        .resolve("/a_much_longer_name.txt", None)
+        .unwrap()
+        .file;
+    assert_eq!(after.start_cluster, before.start_cluster);
+    assert_eq!(after.modified, before.modified);
+    assert_eq!(fs_lock.read_file_info(&after).unwrap(), data);
+
+    // Retour à un nom court : les slots LFN sont libérés
+    fs_lock
+        .rename_preserving_clusters("/a_much_longer_name.txt", "B.TXT")
+        .unwrap();
+    assert_eq!(
+        fs_lock.read_file_info(&fs_lock.resolve("/B.TXT", None).unwrap().file),
+        Ok(data.to_vec())
+    );
+
+    assert_eq!(
+        fs_lock.rename_preserving_clusters("/B.TXT", "test.txt"),
+        Err(FsError::AlreadyExists)
+    );
+    assert_eq!(
+        fs_lock.rename_preserving_clusters("/B.TXT", "a/b"),
+        Err(FsError::InvalidName)
+    );
+}
+
+#[test_case]
+fn read_only_mount_test() {
+    let disk = alloc::vec::Vec::from(DISK_IMAGE).into_boxed_slice();
+    let opts = MountOptions {
+        read_only: true,
+        ..MountOptions::default()
+    };
+    let fs = Rc::new(Mutex::new(Fat32FileSystem::new_with_options(disk, opts)));
+    let shell = ShellSession::new(fs.clone());
+    let before = fs.lock().checksum();
+
+    assert_eq!(
+        shell.touch("", "new.txt"),
+        Err(FsError::ReadOnly.to_string())
+    );
+    assert_eq!(shell.rm("test.txt"), Err(FsError::ReadOnly));
+    assert_eq!(
+        fs.lock().append_file("/test.txt", b"x"),
+        Err(FsError::ReadOnly)
+    );
+    assert_eq!(fs.lock().checksum(), before);
+
+    shell.start_capture();
+    shell.cat("test_dir/test_dir_file").unwrap();
+    assert_eq!(
+        shell.take_output(),
+        "test d'écriture dans un fichier d'un dossier\n\n"
+    );
+}
+
+#[test_case]
+fn find_by_cluster_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+    let file = fs_lock
+        .resolve("/test_dir/test_dir_file", None)
+        .unwrap()
+        .file;
+
+    assert_eq!(
+        fs_lock.find_by_cluster(file.start_cluster).as_deref(),
+        Some("/test_dir/test_dir_file")
+    );
+    assert_eq!(
+        fs_lock.find_by_cluster(fs_lock.root_cluster).as_deref(),
+        Some("/")
+    );
+    assert_eq!(fs_lock.find_by_cluster(500), None);
+}
+
+#[test_case]
+fn cyclic_directory_chain_test() {
+    // Racine sur deux clusters (2 puis 3) dont le second reboucle sur le premier,
+    // sans marqueur 0x00 de fin d’entrées
+    let mut disk = alloc::vec![0u8; 6 * 512];
+
+    set_fat_entry(&mut disk, 2, 3);
+    set_fat_entry(&mut disk, 3, 2);
+    for slot in disk[2 * 512..4 * 512].chunks_exact_mut(32) {
+        slot[0..11].copy_from_slice(b"LOOP    TXT");
+        slot[11] = 0x20;
+    }
+
+    let fs = Fat32FileSystem::new_from_parts(disk.into_boxed_slice(), 512, 1, 1, 2, 2);
+
+    assert_eq!(
+        try_list_directory_entries(&fs, fs.root_cluster).unwrap_err(),
+        FsError::CorruptChain
+    );
+    assert_eq!(
+        fs.checked_cluster_chain(fs.root_cluster),
+        Err(FsError::CorruptChain)
+    );
+    assert_eq!(list_directory_entries(&fs, fs.root_cluster).len(), 32);
+
+    // La résolution et `ls` signalent la chaîne cyclique au lieu de s’arrêter en silence
+    assert!(fs.resolve("/LOOP.TXT", None).is_ok());
+    assert_eq!(
+        fs.resolve("/MISSING.TXT", None).unwrap_err(),
+        FsError::CorruptChain
+    );
+    let mut entries = fs.dir_iter(fs.root_cluster);
+    assert_eq!(entries.by_ref().count(), 32);
+    assert_eq!(entries.check_chain(), Err(FsError::CorruptChain));
+
+    let mut shell = ShellSession::new(Rc::new(Mutex::new(fs)));
+    shell.start_capture();
+    assert_eq!(shell.ls(None), Err(FsError::CorruptChain));
+    assert!(shell.take_output().starts_with("> [FILE] LOOP.TXT"));
+    assert_eq!(shell.cd("MISSING"), Err(FsError::CorruptChain));
+
+    let sane = init_fs();
+    let sane_lock = sane.lock();
+    assert_eq!(
+        try_list_directory_entries(&sane_lock, sane_lock.root_cluster).unwrap(),
+        list_directory_entries(&sane_lock, sane_lock.root_cluster)
+    );
+}
+
+#[test_case]
+fn write_file_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+    let cluster_bytes = (fs_lock.bytes_per_sector * fs_lock.sectors_per_cluster) as usize;
+    let free_before = fs_lock.recompute_free_count();
+
+    // Données binaires (non UTF-8) sur trois clusters, fichier créé à la volée
+    let data: alloc::vec::Vec<u8> = (0..cluster_bytes * 2 + 10)
+        .map(|i| (i % 251) as u8 | 0x80)
+        .collect();
+    fs_lock
+        .write_file("/test_dir/BIN.DAT", None, &data)
+        .unwrap();
+
+    assert_eq!(
+        fs_lock.read_file_bytes("/test_dir/BIN.DAT", None).unwrap(),
+        data
+    );
+    assert_eq!(
+        fs_lock.read_file("/test_dir/BIN.DAT", None),
+        Err(FsError::InvalidUtf8)
+    );
+    assert_eq!(fs_lock.verify_file("/test_dir/BIN.DAT"), Ok(()));
+
+    // Réécriture plus courte : les clusters en surplus sont libérés
+    let dir = fs_lock.resolve("/test_dir", None).unwrap().file;
+    fs_lock
+        .write_file("BIN.DAT", Some(dir.start_cluster), b"short")
+        .unwrap();
+    assert_eq!(
+        fs_lock.read_file_bytes("/test_dir/BIN.DAT", None).unwrap(),
+        b"short"
+    );
+    assert_eq!(fs_lock.verify_file("/test_dir/BIN.DAT"), Ok(()));
+    assert_eq!(fs_lock.recompute_free_count(), free_before - 1);
+
+    assert_eq!(
+        fs_lock.write_file("/test_dir", None, b"x"),
+        Err(FsError::NotAFile)
+    );
+    assert_eq!(
+        fs_lock.write_file("/missing/FILE.TXT", None, b"x"),
+        Err(FsError::NotFound)
+    );
+    assert_eq!(
+        fs_lock.write_file("/test.txt/FILE.TXT", None, b"x"),
+        Err(FsError::NotADirectory)
+    );
+
+    // Minuscules et noms longs : le chemin fourni désigne le fichier créé
+    for name in ["/notes.txt", "/a long report.text"] {
+        fs_lock.write_file(name, None, b"first").unwrap();
+        fs_lock.write_file(name, None, b"second").unwrap();
+        assert_eq!(fs_lock.read_file(name, None).unwrap(), "second");
+    }
+    assert_eq!(
+        fs_lock.write_file("/NOTES.TXT", None, b"x"),
+        Err(FsError::AlreadyExists)
+    );
+}
+
+#[test_case]
+fn walk_skip_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let mut all = alloc::vec::Vec::new();
+    fs_lock.walk(|_| false, |path, _| all.push(path.to_string()));
+    assert!(all.iter().any(|p| p == "/test_dir/test_dir_file"));
+
+    let mut pruned = alloc::vec::Vec::new();
+    fs_lock.walk(
+        |file| file.is_directory && file.name == "test_dir",
+        |path, _| pruned.push(path.to_string()),
+    );
+    assert!(pruned.iter().any(|p| p == "/test.txt"));
+    assert!(!pruned.iter().any(|p| p.starts_with("/test_dir")));
+}
+
+#[test_case]
+fn fat_size16_fallback_test() {
+    // FATSz32 nul : la taille est reprise de FATSz16
+    let mut disk = alloc::vec::Vec::from(DISK_IMAGE).into_boxed_slice();
+    let reserved = u16::from_le_bytes(disk[14..16].try_into().unwrap()) as u32;
+    let num_fats = disk[16] as u32;
+    let fat_size = u32::from_le_bytes(disk[36..40].try_into().unwrap());
+    disk[36..40].copy_from_slice(&0u32.to_le_bytes());
+    disk[22..24].copy_from_slice(&(fat_size as u16).to_le_bytes());
+
+    let fs = Fat32FileSystem::try_new(disk).unwrap();
+    assert_eq!(fs.sectors_per_fat, fat_size);
+    assert_eq!(fs.data_sector, reserved + num_fats * fat_size);
+    assert_eq!(fs.read_file("/test.txt", None).unwrap().len(), 5);
+
+    // Les deux champs nuls : la zone de données chevaucherait la FAT
+    let mut disk = fs.disk;
+    disk[22..24].copy_from_slice(&0u16.to_le_bytes());
+    assert_eq!(
+        Fat32FileSystem::try_new(disk).err(),
+        Some(FsError::BadGeometry)
+    );
+}
+
+#[test_case]
+fn cp_disk_full_test() {
+    let mut fs = blank_volume(1 << 20);
+    let data = alloc::vec![5u8; 600 * 1024];
+    fs.create_file("", "src.bin").unwrap();
+    fs.write_file("/SRC.BIN", None, &data).unwrap();
+
+    // La copie échoue à mi-chemin : ni l’entrée (nom long compris) ni ses clusters ne restent
+    let free = fs.recompute_free_count();
+    assert_eq!(
+        fs.copy_file("/SRC.BIN", "/a long copy name.bin", None, false, None),
+        Err(FsError::DiskFull)
+    );
+    assert_eq!(fs.recompute_free_count(), free);
+    assert_eq!(
+        fs.resolve("/a long copy name.bin", None).err(),
+        Some(FsError::NotFound)
+    );
+    assert_eq!(list_directory_entries(&fs, fs.root_cluster).len(), 1);
+    let report = fs.check();
+    assert!(report.is_clean(), "{:?}", report.warnings);
+}
+
+#[test_case]
+fn cp_progress_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    let cluster_bytes = {
+        let fs_lock = fs.lock();
+        (fs_lock.bytes_per_sector * fs_lock.sectors_per_cluster) as usize
+    };
+
+    let data: alloc::vec::Vec<u8> = (0..cluster_bytes * 3 + 7).map(|i| i as u8).collect();
+    fs.lock().write_file("/BIG.BIN", None, &data).unwrap();
+
+    let mut calls = alloc::vec::Vec::new();
+    shell
+        .cp(
+            "BIG.BIN",
+            "COPY.BIN",
+            false,
+            Some(&mut |done, total| calls.push((done, total))),
+        )
+        .unwrap();
+    assert_eq!(calls.len(), 4);
+    assert_eq!(calls.last(), Some(&(data.len() as u64, data.len() as u64)));
+    assert_eq!(fs.lock().read_file_bytes("/COPY.BIN", None).unwrap(), data);
+
+    // Copie récursive : le total couvre tous les fichiers de l’arborescence
+    let mut last = (0, 0);
+    shell
+        .cp_r(
+            "/test_dir",
+            "/DIR_COPY",
+            false,
+            Some(&mut |done, total| last = (done, total)),
+        )
+        .unwrap();
+    assert_eq!(last.0, last.1);
+    let original = fs
+        .lock()
+        .read_file("/test_dir/test_dir_file", None)
+        .unwrap();
+    assert_eq!(
+        fs.lock().read_file("/DIR_COPY/test_dir_file", None),
+        Ok(original)
+    );
+    assert_eq!(
+        shell.cp("/test.txt", "/test_dir/test_dir_file", false, None),
+        Err(FsError::AlreadyExists)
+    );
+
+    let mut moved = (0, 0);
+    shell
+        .mv(
+            "COPY.BIN",
+            "/DIR_COPY",
+            Some(&mut |done, total| moved = (done, total)),
+        )
+        .unwrap();
+    assert_eq!(moved, (data.len() as u64, data.len() as u64));
+    assert!(fs.lock().resolve("/COPY.BIN", None).is_err());
+    assert_eq!(
+        fs.lock()
+            .read_file_bytes("/DIR_COPY/COPY.BIN", None)
+            .unwrap(),
+        data
+    );
+
+    shell.run_command("mv /DIR_COPY /test_dir").unwrap();
+    shell.cd("/test_dir/DIR_COPY").unwrap();
+    shell.cd("..").unwrap();
+    assert_eq!(shell.current_path, "/test_dir");
+    assert_eq!(
+        shell.mv("/test_dir", "/test_dir/DIR_COPY", None),
+        Err(FsError::InvalidName)
+    );
+    assert!(fs.lock().find_cross_links().is_empty());
+    assert_eq!(fs.lock().verify_file("/test_dir/DIR_COPY/COPY.BIN"), Ok(()));
+}
+
+#[test_case]
+fn cp_preserve_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    fs.lock().set_clock(Rc::new(FixedClock));
+
+    let source = fs.lock().resolve("/test.txt", None).unwrap().file;
+    assert_ne!(source.modified, FixedClock.now());
+
+    shell.cp("/test.txt", "/FRESH.TXT", false, None).unwrap();
+    shell.run_command("cp -p /test.txt /KEPT.TXT").unwrap();
+
+    let fresh = fs.lock().resolve("/FRESH.TXT", None).unwrap().file;
+    assert_eq!(fresh.modified, FixedClock.now());
+
+    let kept = fs.lock().resolve("/KEPT.TXT", None).unwrap().file;
+    assert_eq!(kept.created, source.created);
+    assert_eq!(kept.modified, source.modified);
+
+    // Copie récursive : le répertoire et ses fichiers gardent leurs dates
+    shell.cp_r("/test_dir", "/DIR_KEPT", true, None).unwrap();
+    let dir = fs.lock().resolve("/test_dir", None).unwrap().file;
+    let dir_copy = fs.lock().resolve("/DIR_KEPT", None).unwrap().file;
+    assert_eq!(dir_copy.modified, dir.modified);
+
+    let file = fs
+        .lock()
+        .resolve("/test_dir/test_dir_file", None)
+        .unwrap()
+        .file;
+    let file_copy = fs
+        .lock()
+        .resolve("/DIR_KEPT/test_dir_file", None)
+        .unwrap()
+        .file;
+    assert_eq!(file_copy.created, file.created);
+    assert_eq!(file_copy.modified, file.modified);
+}
+
+#[test_case]
+fn open_dir_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let dir = fs_lock.open_dir("/test_dir", None).unwrap();
+    assert!(dir.exists("test_dir_file"));
+    assert!(!dir.exists("missing"));
+    assert!(!dir.exists(".."));
+    assert_eq!(dir.entries().len(), 1);
+    assert_eq!(dir.iter().next().unwrap().name, "test_dir_file");
+
+    let root = fs_lock.open_dir("/", None).unwrap();
+    assert_eq!(root.cluster(), fs_lock.root_cluster);
+    assert!(root.exists("test_dir"));
+
+    let parent = fs_lock.open_dir("..", Some(dir.cluster())).unwrap();
+    assert_eq!(parent.cluster(), fs_lock.root_cluster);
+
+    assert_eq!(
+        fs_lock.open_dir("/test.txt", None).err(),
+        Some(FsError::NotADirectory)
+    );
+    assert_eq!(
+        fs_lock.open_dir("/missing", None).err(),
+        Some(FsError::NotFound)
+    );
+}
+
+#[test_case]
+fn complete_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs);
+
+    let mut candidates = shell.complete("test");
+    candidates.sort();
+    assert_eq!(candidates, ["test.txt", "test_dir/"]);
+
+    assert_eq!(shell.complete("TEST_D"), ["test_dir/"]);
+    assert_eq!(shell.complete("/test_dir/te"), ["test_dir_file"]);
+    assert_eq!(
+        shell.complete("/missing/te"),
+        alloc::vec::Vec::<alloc::string::String>::new()
+    );
+
+    shell.cd("test_dir").unwrap();
+    assert_eq!(shell.complete("test_dir_"), ["test_dir_file"]);
+    assert_eq!(shell.complete("../test."), ["test.txt"]);
+}
+
+#[test_case]
+fn size_without_cluster_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+    assert!(
+        !fs_lock
+            .check()
+            .warnings
+            .iter()
+            .any(|w| matches!(w, FsWarning::SizeWithoutCluster { .. }))
+    );
+
+    // Entrée « FAT16 » : seul le mot bas du cluster a été écrit, ici à 0
+    let location = fs_lock.locate_entry("/test.txt", None).unwrap();
+    let offset = (fs_lock.cluster_to_sector(location.cluster).unwrap() * fs_lock.bytes_per_sector)
+        as usize
+        + location.offset_in_cluster;
+    let size = fs_lock.resolve("/test.txt", None).unwrap().file.size;
+    fs_lock.disk[offset + 20..offset + 22].fill(0);
+    fs_lock.disk[offset + 26..offset + 28].fill(0);
+
+    let report = fs_lock.check();
+    assert!(report.warnings.contains(&FsWarning::SizeWithoutCluster {
+        path: "/test.txt".into(),
+        size,
+    }));
+
+    let quiet = fs_lock.check_with(&CheckOptions {
+        entries: false,
+        ..CheckOptions::default()
+    });
+    assert!(
+        !quiet
+            .warnings
+            .iter()
+            .any(|w| matches!(w, FsWarning::SizeWithoutCluster { .. }))
+    );
+}
+
+#[test_case]
+fn file_writer_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+
+    let expected: alloc::vec::Vec<u8> = (0..10 * 1024).map(|i| (i * 7 % 256) as u8).collect();
+
+    let mut writer = fs_lock.create_writer("/test_dir/STREAM.BIN").unwrap();
+    for chunk in expected.chunks(100) {
+        writer.write(chunk).unwrap();
+    }
+    writer.finish().unwrap();
+
+    assert_eq!(
+        fs_lock
+            .read_file_bytes("/test_dir/STREAM.BIN", None)
+            .unwrap(),
+        expected
+    );
+    assert_eq!(fs_lock.verify_file("/test_dir/STREAM.BIN"), Ok(()));
+
+    // Une seconde ouverture vide le fichier
+    let mut writer = fs_lock.create_writer("/test_dir/STREAM.BIN").unwrap();
+    writer.write(b"tail").unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+        fs_lock
+            .read_file_bytes("/test_dir/STREAM.BIN", None)
+            .unwrap(),
+        b"tail"
+    );
+
+    let mut writer = fs_lock.create_writer("/test_dir/stream log.bin").unwrap();
+    writer.write(b"long name").unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+        fs_lock
+            .read_file_bytes("/test_dir/stream log.bin", None)
+            .unwrap(),
+        b"long name"
+    );
+}
+
+#[test_case]
+fn read_file_with_meta_test() {
+    let fs = init_fs();
+    let fs_lock = fs.lock();
+
+    let (file, data) = fs_lock.read_file_with_meta("/test.txt", None).unwrap();
+    assert_eq!(file.name, "test.txt");
+    assert_eq!(file.size as usize, data.len());
+    assert_eq!(data, fs_lock.read_file_bytes("/test.txt", None).unwrap());
+
+    let dir = fs_lock.resolve("/test_dir", None).unwrap().file;
+    let (file, _) = fs_lock
+        .read_file_with_meta("test_dir_file", Some(dir.start_cluster))
+        .unwrap();
+    assert_eq!(file.name, "test_dir_file");
+
+    assert_eq!(
+        fs_lock.read_file_with_meta("/test_dir", None).err(),
+        Some(FsError::NotAFile)
+    );
+}
+
+#[test_case]
+fn unaligned_disk_test() {
+    // Capacité exacte : une seule image de 32 Mio sur le tas à la fois
+    let mut image = alloc::vec::Vec::with_capacity(DISK_IMAGE.len() + 100);
+    image.extend_from_slice(DISK_IMAGE);
+    image.extend_from_slice(&[0xAA; 100]);
+
+    let fs = Fat32FileSystem::try_new(image.into_boxed_slice()).unwrap();
+    assert_eq!(fs.trailing_bytes(), 100);
+    assert!(
+        fs.check()
+            .warnings
+            .contains(&FsWarning::UnalignedDisk { trailing: 100 })
+    );
+    assert!(fs.read_file("/test.txt", None).is_ok());
+
+    let strict = MountOptions {
+        strict_geometry: true,
+        ..MountOptions::default()
+    };
+    assert_eq!(
+        Fat32FileSystem::try_new_with_options(fs.disk, strict).err(),
+        Some(FsError::BadGeometry)
+    );
+
+    let aligned = alloc::vec::Vec::from(DISK_IMAGE).into_boxed_slice();
+    let fs = Fat32FileSystem::try_new_with_options(aligned, strict).unwrap();
+    assert_eq!(fs.trailing_bytes(), 0);
+    assert!(fs.mount_options().strict_geometry);
+}
+
+#[test_case]
+fn wipe_on_free_test() {
+    for wipe in [false, true] {
+        let fs = init_fs();
+        let mut fs_lock = fs.lock();
+        fs_lock.options.wipe_on_free = wipe;
+
+        fs_lock
+            .write_file("/SECRET.TXT", None, b"top secret")
+            .unwrap();
+        let cluster = fs_lock
+            .resolve("/SECRET.TXT", None)
+            .unwrap()
+            .file
+            .start_cluster;
+        fs_lock.remove_file("/SECRET.TXT", None).unwrap();
+
+        let data = fs_lock.read_cluster(cluster);
+        if wipe {
+            assert!(data.iter().all(|&b| b == 0));
+        } else {
+            assert_eq!(&data[..10], b"top secret");
+        }
+    }
+}
+
+#[test_case]
+fn stat_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    fs.lock().set_clock(Rc::new(FixedClock));
+
+    // 1300 octets sur des clusters de 512 octets : trois clusters alloués
+    let data = alloc::vec![b'x'; 1300];
+    fs.lock().write_file("/MULTI.BIN", None, &data).unwrap();
+
+    shell.start_capture();
+    shell.run_command("stat MULTI.BIN").unwrap();
+    let output = shell.take_output();
+
+    let start = fs
+        .lock()
+        .resolve("/MULTI.BIN", None)
+        .unwrap()
+        .file
+        .start_cluster;
+    assert!(output.contains("  File: MULTI.BIN\n"));
+    assert!(output.contains("  Type: file\n"));
+    assert!(output.contains("  Size: 1300 (allocated 1536)\n"));
+    assert!(output.contains(&alloc::format!(
+        "Cluster: {} (chain of 3 clusters)\n",
+        start
+    )));
+    assert!(output.contains(" Attrs: ----A\n"));
+    assert!(output.contains("Modify: 2024-05-17 13:45:30\n"));
+
+    shell.start_capture();
+    shell.stat("/test_dir").unwrap();
+    assert!(shell.take_output().contains("  Type: directory\n"));
+    assert_eq!(shell.stat("/missing"), Err(FsError::NotFound));
+}
+
+#[test_case]
+fn trailing_slash_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+
+    assert_eq!(
+        fs.lock().read_file("/test.txt/", None),
+        Err(FsError::NotADirectory)
+    );
+    assert!(shell.run_command("cat /test.txt/").is_err());
+
+    shell.start_capture();
+    assert_eq!(shell.ls(Some("/test_dir/")), Ok(()));
+    assert!(shell.take_output().contains("test_dir_file"));
+    assert_eq!(shell.cd("test_dir/"), Ok(()));
+    assert_eq!(shell.current_path, "/test_dir");
+}
+
+#[test_case]
+fn xxd_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    fs.lock()
+        .write_file("/HEX.BIN", None, b"Hello, FAT32!\x00\x01\xffmore")
+        .unwrap();
+
+    shell.start_capture();
+    shell.xxd("/HEX.BIN", 16).unwrap();
+    assert_eq!(
+        shell.take_output(),
+        "00000000: 4865 6c6c 6f2c 2046 4154 3332 2100 01ff  Hello, FAT32!...\n"
+    );
+
+    shell.start_capture();
+    shell.run_command("xxd HEX.BIN").unwrap();
+    assert_eq!(
+        shell.take_output().lines().nth(1),
+        Some("00000010: 6d6f 7265                                more")
+    );
+
+    let cluster = fs
+        .lock()
+        .resolve("/HEX.BIN", None)
+        .unwrap()
+        .file
+        .start_cluster;
+    shell.start_capture();
+    shell.xxd_cluster(cluster).unwrap();
+    let output = shell.take_output();
+    assert_eq!(output.lines().count(), 512 / 16);
+    assert!(output.starts_with("00000000: 4865 6c6c"));
+
+    assert_eq!(shell.xxd_cluster(1), Err(FsError::InvalidCluster));
+    assert_eq!(
+        format_hexdump_line(32, b""),
+        alloc::format!("00000020:{}  ", " ".repeat(40))
+    );
+}
+
+#[test_case]
+fn parent_of_root_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+
+    shell.cd("/..").unwrap();
+    assert_eq!(shell.current_path, "/");
+    assert_eq!(shell.current_cluster, fs.lock().root_cluster);
+
+    shell.cd("test_dir").unwrap();
+    shell.cd("../..").unwrap();
+    assert_eq!(shell.current_path, "/");
+    assert_eq!(shell.current_cluster, fs.lock().root_cluster);
+
+    let fs = fs.lock();
+    assert_eq!(
+        fs.read_file("/../test.txt", None),
+        fs.read_file("/test.txt", None)
+    );
+    assert!(fs.read_file("/../../test.txt", None).is_ok());
+    assert!(fs.read_file("/test_dir/../../test.txt", None).is_ok());
+}
+
+#[test_case]
+fn iter_free_clusters_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+
+    {
+        let fs = fs.lock();
+        let free: alloc::vec::Vec<u32> = fs.iter_free_clusters().collect();
+        assert_eq!(free.len() as u32, fs.recompute_free_count());
+        assert!(free.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(free.iter().all(|&cluster| fs.is_valid_cluster(cluster)));
+    }
+
+    let free_before = fs.lock().iter_free_clusters().count();
+    fs.lock().write_file("/FREE.TXT", None, b"x").unwrap();
+    let cluster = fs
+        .lock()
+        .resolve("/FREE.TXT", None)
+        .unwrap()
+        .file
+        .start_cluster;
+    assert_eq!(fs.lock().iter_free_clusters().count(), free_before - 1);
+    assert!(fs.lock().iter_free_clusters().all(|c| c != cluster));
+
+    shell.start_capture();
+    shell.run_command("freemap").unwrap();
+    let output = shell.take_output();
+    let count: u32 = output
+        .trim_end()
+        .split(", ")
+        .map(|range| match range.split_once('-') {
+            Some((start, end)) => end.parse::<u32>().unwrap() - start.parse::<u32>().unwrap() + 1,
+            None => 1,
+        })
+        .sum();
+    let expected = fs.lock().recompute_free_count();
+    assert_eq!(count, expected);
+}
+
+#[test_case]
+fn relocate_cluster_test() {
+    let fs = init_fs();
+    let mut fs = fs.lock();
+    let content = fs.read_file("/test.txt", None).unwrap();
+    let old = fs.resolve("/test.txt", None).unwrap().file.start_cluster;
+    let new = fs.iter_free_clusters().last().unwrap();
+
+    fs.relocate_cluster(old, new, None).unwrap();
+    assert_eq!(
+        fs.resolve("/test.txt", None).unwrap().file.start_cluster,
+        new
+    );
+    assert_eq!(fs.read_file("/test.txt", None).unwrap(), content);
+    assert!(fs.iter_free_clusters().any(|c| c == old));
+    assert!(fs.find_cross_links().is_empty());
+
+    // `new` n’est plus libre, `old` ne débute plus aucune chaîne
+    assert_eq!(
+        fs.relocate_cluster(old, new, None),
+        Err(FsError::InvalidCluster)
+    );
+    assert_eq!(
+        fs.relocate_cluster(new, old, Some(old)),
+        Err(FsError::InvalidCluster)
+    );
+    let root = fs.root_cluster;
+    assert_eq!(
+        fs.relocate_cluster(root, old, None),
+        Err(FsError::InvalidCluster)
+    );
+
+    // Premier cluster d’un répertoire : `.` et le `..` des enfants suivent
+    fs.mkdir("/", "A").unwrap();
+    fs.mkdir("/A", "B").unwrap();
+    let dir = fs.resolve("/A", None).unwrap().file.start_cluster;
+    let target = fs.iter_free_clusters().last().unwrap();
+    fs.relocate_cluster(dir, target, None).unwrap();
+    assert_eq!(
+        fs.resolve("/A/B/..", None).unwrap().file.start_cluster,
+        target
+    );
+    let dot = try_list_directory_entries(&fs, target)
+        .unwrap()
+        .into_iter()
+        .find(|f| f.name == ".")
+        .unwrap();
+    assert_eq!(dot.start_cluster, target);
+    assert!(fs.open_dir("/A/B", None).is_ok());
+}
+
+#[test_case]
+fn lazy_ls_test() {
+    let (fs, device) = init_fs_with_hook();
+    let mut shell = ShellSession::new(fs.clone());
+
+    let cluster = {
+        let mut fs = fs.lock();
+        fs.mkdir("/", "BIG").unwrap();
+        for i in 0..100 {
+            fs.write_file(&alloc::format!("/BIG/F{}.TXT", i), None, b"")
+                .unwrap();
+        }
+        fs.resolve("/BIG", None).unwrap().file.start_cluster
+    };
+
+    let chain_length = fs.lock().cluster_chain(cluster).len() as u64;
+    assert!(chain_length > 4);
+
+    {
+        let fs = fs.lock();
+
+        // La première entrée ne coûte que le premier cluster (et son secteur de FAT)
+        let before = device.reads();
+        let mut entries = fs.dir_iter(cluster);
+        assert_eq!(entries.next().map(|f| f.name), Some(".".to_string()));
+        assert!(device.reads() - before <= 2);
+
+        let before = device.reads();
+        assert_eq!(entries.count(), 101);
+        assert!(device.reads() - before >= chain_length - 1);
+
+        let listed: alloc::vec::Vec<FileInfo> = fs.dir_iter(cluster).collect();
+        assert_eq!(listed, list_directory_entries(&fs, cluster));
+
+        // Le handle de `open_dir` s’arrête lui aussi à la première entrée trouvée
+        let dir = fs.open_dir("/BIG", None).unwrap();
+        let before = device.reads();
+        assert!(dir.exists("F0.TXT"));
+        assert!(device.reads() - before <= 2);
+        assert_eq!(dir.entries().len(), 100);
+    }
+
+    // La première ligne de `ls` est émise avant la lecture du reste de la chaîne
+    // (le compteur du périphérique se lit sans verrouiller le système de fichiers)
+    let first_line = Rc::new(core::cell::Cell::new(None));
+    let recorded = first_line.clone();
+    let counter = device.clone();
+    shell.on_emit = Some(alloc::boxed::Box::new(move |text: &str| {
+        if text.starts_with("[FILE]") && recorded.get().is_none() {
+            recorded.set(Some(counter.reads()));
+        }
+    }));
+
+    let before = device.reads();
+    shell.start_capture();
+    shell.ls(Some("/BIG")).unwrap();
+    let first_line = first_line.get().unwrap() - before;
+    assert!(first_line < device.reads() - before);
+    assert!(device.reads() - before - first_line >= chain_length - 1);
+
+    let output = shell.take_output();
+    assert!(output.starts_with("> [FILE] F0.TXT [FILE] F1.TXT "));
+    assert_eq!(output.matches("[FILE]").count(), 100);
+}
+
+#[test_case]
+fn sync_size_from_chain_test() {
+    let fs = init_fs();
+    let mut fs_lock = fs.lock();
+    let content = fs_lock.read_file("/test.txt", None).unwrap();
+    assert_eq!(
+        fs_lock.sync_size_from_chain("/test.txt", false),
+        Ok(content.len() as u32)
+    );
+
+    // test.txt n’occupe qu’un cluster de 512 octets mais en déclare 2000
+    let size_offset = (fs_lock.data_sector * fs_lock.bytes_per_sector) as usize + 32 + 28;
+    fs_lock.disk[size_offset..size_offset + 4].copy_from_slice(&2000u32.to_le_bytes());
+
+    assert_eq!(fs_lock.sync_size_from_chain("/test.txt", false), Ok(512));
+    assert_eq!(fs_lock.metadata("/test.txt", None).unwrap().size, 512);
+    assert_eq!(fs_lock.verify_file("/test.txt"), Ok(()));
+
+    // Taille nulle masquant les données : seuls les octets nuls de fin sont exclus
+    fs_lock.write_file("/DATA.BIN", None, &[7u8; 700]).unwrap();
+    let location = fs_lock.locate_entry("/DATA.BIN", None).unwrap();
+    let offset = (fs_lock.cluster_to_sector(location.cluster).unwrap() * fs_lock.bytes_per_sector)
+        as usize
+        + location.offset_in_cluster
+        + 28;
+    fs_lock.disk[offset..offset + 4].copy_from_slice(&0u32.to_le_bytes());
+
+    assert_eq!(fs_lock.sync_size_from_chain("/DATA.BIN", false), Ok(700));
+    assert_eq!(
+        fs_lock.read_file_bytes("/DATA.BIN", None).unwrap(),
+        [7u8; 700]
+    );
+
+    fs_lock.disk[offset..offset + 4].copy_from_slice(&0u32.to_le_bytes());
+    assert_eq!(fs_lock.sync_size_from_chain("/DATA.BIN", true), Ok(1024));
+    assert_eq!(
+        fs_lock.sync_size_from_chain("/test_dir", false),
+        Err(FsError::NotAFile)
+    );
+}
+
+#[test_case]
+fn empty_file_without_cluster_test() {
+    let fs = init_fs();
+    let shell = ShellSession::new(fs.clone());
+    let free_before = fs.lock().recompute_free_count();
+
+    shell.touch("", "EMPTY_T").expect("Erreur lors du touch");
+    let file = fs.lock().metadata("/EMPTY_T", None).unwrap();
+    assert_eq!((file.size, file.start_cluster), (0, 0));
+    assert_eq!(
+        fs.lock().read_file("/EMPTY_T", None),
+        Ok(alloc::string::String::new())
+    );
+    assert_eq!(fs.lock().verify_file("/EMPTY_T"), Ok(()));
+    assert_eq!(fs.lock().recompute_free_count(), free_before);
+
+    // Le premier cluster est alloué à la première écriture…
+    shell
+        .write("EMPTY_T", "data")
+        .expect("erreur lors du write");
+    assert_ne!(
+        fs.lock().metadata("/EMPTY_T", None).unwrap().start_cluster,
+        0
+    );
+    assert_eq!(fs.lock().recompute_free_count(), free_before - 1);
+
+    // … et libéré lorsque le fichier est ramené à 0 octet
+    fs.lock().write_file("/EMPTY_T", None, b"").unwrap();
+    let file = fs.lock().metadata("/EMPTY_T", None).unwrap();
+    assert_eq!((file.size, file.start_cluster), (0, 0));
+    assert_eq!(fs.lock().recompute_free_count(), free_before);
+    assert_eq!(
+        fs.lock().read_file("/EMPTY_T", None),
+        Ok(alloc::string::String::new())
+    );
+}
+
+#[test_case]
+fn ls_markers_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    {
+        let mut fs = fs.lock();
+        fs.mkdir("/", "MARK").unwrap();
+        fs.write_file("/MARK/PLAIN.TXT", None, b"plain").unwrap();
+        fs.write_file("/MARK/SYS.BIN", None, b"system").unwrap();
+        fs.mkdir("/MARK", "SUB").unwrap();
+
+        let location = fs.locate_entry("/MARK/SYS.BIN", None).unwrap();
+        let offset = (fs.cluster_to_sector(location.cluster).unwrap() * fs.bytes_per_sector)
+            as usize
+            + location.offset_in_cluster
+            + 11;
+        fs.disk[offset] |= ATTR_SYSTEM;
+    }
+
+    shell.classify = true;
+    shell.start_capture();
+    shell.ls(Some("/MARK")).unwrap();
+    assert_eq!(shell.take_output(), "> PLAIN.TXT SYS.BIN* SUB/ \n");
+
+    shell.color = true;
+    shell.ls(Some("/MARK")).unwrap();
+    assert_eq!(
+        shell.take_output(),
+        "> PLAIN.TXT \x1b[31mSYS.BIN\x1b[0m* \x1b[1;34mSUB\x1b[0m/ \n"
+    );
+
+    // Les séquences ANSI ne faussent pas l’alignement en colonnes
+    shell.terminal_width = 80;
+    shell.ls(Some("/MARK")).unwrap();
+    assert_eq!(
+        shell.take_output(),
+        "PLAIN.TXT  \x1b[1;34mSUB\x1b[0m/       \x1b[31mSYS.BIN\x1b[0m*\n"
+    );
+
+    shell.classify = false;
+    shell.color = false;
+    shell.terminal_width = 0;
+    shell.ls(Some("/MARK")).unwrap();
+    assert_eq!(
+        shell.take_output(),
+        "> [FILE] PLAIN.TXT [FILE] SYS.BIN [DIR] SUB \n"
+    );
+
+    let sys = fs.lock().metadata("/MARK/SYS.BIN", None).unwrap();
+    assert_eq!(type_marker(&sys), "*");
+    assert_eq!(type_marker(&fs.lock().root_info()), "/");
+}
+
+#[test_case]
+fn dir_free_slots_test() {
+    let fs = init_fs();
+    let mut fs = fs.lock();
+    fs.mkdir("/", "SLOTS").unwrap();
+    let dir = fs.resolve("/SLOTS", None).unwrap().file.start_cluster;
+    let slots_per_cluster = (fs.sectors_per_cluster * fs.bytes_per_sector) as usize / 32;
+
+    // `.` et `..` occupent les deux premiers slots
+    assert_eq!(fs.dir_free_slots(dir), slots_per_cluster - 2);
+
+    fs.write_file("/SLOTS/A.TXT", None, b"a").unwrap();
+    assert_eq!(fs.dir_free_slots(dir), slots_per_cluster - 3);
+
+    // Une entrée supprimée (`0xE5`) libère son slot
+    fs.remove_file("/SLOTS/A.TXT", None).unwrap();
+    assert_eq!(fs.dir_free_slots(dir), slots_per_cluster - 2);
+
+    for i in 0..slots_per_cluster - 2 {
+        fs.write_file(&alloc::format!("/SLOTS/F{}.TXT", i), None, b"")
+            .unwrap();
+    }
+    assert_eq!(fs.dir_free_slots(dir), 0);
+
+    fs.write_file("/SLOTS/MORE.TXT", None, b"").unwrap();
+    assert_eq!(fs.cluster_chain(dir).len(), 2);
+    assert_eq!(fs.dir_free_slots(dir), slots_per_cluster - 1);
+}
+
+#[test_case]
+fn read_file_first_clusters_test() {
+    let fs = init_fs();
+    let mut fs = fs.lock();
+    let content: alloc::vec::Vec<u8> = (0..1500u32).map(|i| (i % 251) as u8).collect();
+    fs.write_file("/BIG.BIN", None, &content).unwrap();
+    let cluster_bytes = (fs.sectors_per_cluster * fs.bytes_per_sector) as usize;
+
+    let first = fs.read_file_first_clusters("/BIG.BIN", 1).unwrap();
+    assert_eq!(first.len(), cluster_bytes);
+    assert_eq!(first, content[..cluster_bytes]);
+
+    assert_eq!(
+        fs.read_file_first_clusters("/BIG.BIN", 10).unwrap(),
+        content
+    );
+    assert!(
+        fs.read_file_first_clusters("/BIG.BIN", 0)
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        fs.read_file_first_clusters("/test_dir", 1),
+        Err(FsError::NotAFile)
+    );
+
+    fs.options.max_read_bytes = cluster_bytes;
+    assert!(fs.read_file_first_clusters("/BIG.BIN", 1).is_ok());
+    assert_eq!(
+        fs.read_file_first_clusters("/BIG.BIN", 2),
+        Err(FsError::FileTooLarge)
+    );
+}
+
+#[test_case]
+fn truncated_image_test() {
+    let complete = Fat32FileSystem::try_new(DISK_IMAGE.to_vec().into_boxed_slice()).unwrap();
+    assert_eq!(complete.total_sectors() as usize * 512, DISK_IMAGE.len());
+    assert_eq!(complete.missing_bytes(), 0);
+    drop(complete);
+
+    let image = DISK_IMAGE[..DISK_IMAGE.len() - 10 * 512].to_vec();
+
+    // Montage permissif : l’image est acceptée, l’anomalie est signalée par `check`
+    let fs = Fat32FileSystem::try_new(image.into_boxed_slice()).unwrap();
+    assert_eq!(fs.missing_bytes(), 10 * 512);
+    assert!(
+        fs.check()
+            .warnings
+            .contains(&FsWarning::TruncatedImage { missing: 10 * 512 })
+    );
+    assert!(fs.read_file("/test.txt", None).is_ok());
+
+    let strict = MountOptions {
+        strict_geometry: true,
+        ..MountOptions::default()
+    };
+    assert_eq!(
+        Fat32FileSystem::try_new_with_options(fs.disk, strict).err(),
+        Some(FsError::TruncatedImage)
+    );
+}
+
+#[test_case]
+fn mv_directory_dotdot_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    {
+        let mut fs = fs.lock();
+        fs.mkdir("/", "SRC").unwrap();
+        fs.mkdir("/SRC", "INNER").unwrap();
+        fs.mkdir("/", "DST").unwrap();
+    }
+    let dst = fs.lock().resolve("/DST", None).unwrap().file.start_cluster;
+
+    shell.mv("/SRC/INNER", "/DST", None).unwrap();
+    shell.cd("/DST/INNER").unwrap();
+    shell.cd("..").unwrap();
+    assert_eq!(shell.current_cluster, dst);
+    assert_eq!(shell.current_path, "/DST");
+
+    // Vers la racine, `..` vaut 0 et `cd ..` ramène à la racine
+    shell.mv("/DST/INNER", "/", None).unwrap();
+    shell.cd("/INNER").unwrap();
+    shell.cd("..").unwrap();
+    assert_eq!(shell.current_cluster, fs.lock().root_cluster);
+    assert_eq!(shell.current_path, "/");
+}
+
+#[test_case]
+fn alloc_strategy_test() {
+    fn fragments(strategy: AllocStrategy) -> usize {
+        let options = MountOptions {
+            alloc_strategy: strategy,
+            ..MountOptions::default()
+        };
+        let mut fs =
+            Fat32FileSystem::try_new_with_options(DISK_IMAGE.to_vec().into_boxed_slice(), options)
+                .unwrap();
+        assert_eq!(fs.mount_options().alloc_strategy, strategy);
+
+        // Un trou d’un cluster devant B, puis un fichier de trois clusters
+        fs.write_file("/A.BIN", None, &[1u8; 512]).unwrap();
+        fs.write_file("/B.BIN", None, &[2u8; 512]).unwrap();
+        fs.remove_file("/A.BIN", None).unwrap();
+        fs.write_file("/C.BIN", None, &[3u8; 1536]).unwrap();
+        assert_eq!(fs.read_file_bytes("/C.BIN", None).unwrap(), [3u8; 1536]);
+
+        let start = fs.resolve("/C.BIN", None).unwrap().file.start_cluster;
+        let chain = fs.cluster_chain(start);
+        1 + chain
+            .windows(2)
+            .filter(|pair| pair[1] != pair[0] + 1)
+            .count()
+    }
+
+    assert_eq!(fragments(AllocStrategy::NextFit), 1);
+    assert!(fragments(AllocStrategy::FirstFit) > 1);
+}
+
+#[test_case]
+fn verify_layout_test() {
+    let fs = Fat32FileSystem::try_new(DISK_IMAGE.to_vec().into_boxed_slice()).unwrap();
+    assert_eq!(fs.verify_layout(), Ok(()));
+
+    // 200 FAT de 504 secteurs repoussent la zone de données au-delà de l’image ; chaque
+    // étape reprend le disque de la précédente pour n’avoir qu’une image sur le tas
+    let mut image = fs.disk;
+    image[16] = 200;
+    let fs = Fat32FileSystem::try_new(image).unwrap();
+    assert!(fs.data_sector as usize * 512 > DISK_IMAGE.len());
+    assert_eq!(
+        fs.verify_layout(),
+        Err(FsError::BadLayout { region: "data" })
+    );
+
+    let strict = MountOptions {
+        strict_geometry: true,
+        ..MountOptions::default()
+    };
+    assert_eq!(
+        Fat32FileSystem::try_new_with_options(fs.disk, strict).err(),
+        Some(FsError::BadLayout { region: "data" })
+    );
+
+    // Le FSInfo ne peut pas se trouver dans la FAT
+    let mut image = DISK_IMAGE.to_vec();
+    image[48..50].copy_from_slice(&40u16.to_le_bytes());
+    let fs = Fat32FileSystem::try_new(image.into_boxed_slice()).unwrap();
+    assert_eq!(
+        fs.verify_layout(),
+        Err(FsError::BadLayout { region: "reserved" })
+    );
+    assert_eq!(
+        alloc::format!("{}", FsError::BadLayout { region: "fat" }),
+        "Bad volume layout: fat region"
+    );
+}
+
+#[test_case]
+fn touch_parents_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+
+    assert!(shell.touch("/NEWA/NEWB", "FILE.TXT").is_err());
+    assert_eq!(
+        fs.lock().resolve("/NEWA", None).err(),
+        Some(FsError::NotFound)
+    );
+
+    shell.touch_parents("/NEWA/NEWB/FILE.TXT").unwrap();
+    assert_eq!(
+        fs.lock().read_file("/NEWA/NEWB/FILE.TXT", None),
+        Ok(alloc::string::String::new())
+    );
+    shell.write("/NEWA/NEWB/FILE.TXT", "provisioned").unwrap();
+    assert_eq!(
+        fs.lock().read_file("/NEWA/NEWB/FILE.TXT", None).unwrap(),
+        "provisioned"
+    );
+    assert_eq!(
+        shell.touch_parents("/NEWA/NEWB/FILE.TXT"),
+        Err(FsError::AlreadyExists)
+    );
+
+    // Les répertoires existants sont réutilisés, le chemin peut être relatif
+    shell.cd("/NEWA").unwrap();
+    shell.run_command("touch -p NEWB/NEWC/OTHER.TXT").unwrap();
+    assert!(
+        fs.lock()
+            .read_file("/NEWA/NEWB/NEWC/OTHER.TXT", None)
+            .is_ok()
+    );
+    assert_eq!(
+        shell.touch_parents("NEWB/FILE.TXT/X.TXT"),
+        Err(FsError::NotADirectory)
+    );
+}
+
+#[test_case]
+fn iter_dir_raw_test() {
+    let fs = init_fs();
+    let fs = fs.lock();
+    let root = fs.root_cluster;
+
+    let slots_per_cluster = (fs.sectors_per_cluster * fs.bytes_per_sector) as usize / 32;
+    let chain_length = fs.cluster_chain(root).len();
+    assert_eq!(
+        fs.iter_dir_raw(root).count(),
+        chain_length * slots_per_cluster
+    );
+
+    let (entry, cluster, offset) = fs
+        .iter_dir_raw(root)
+        .find(|(entry, _, _)| &entry[0..11] == b"TEST    TXT")
+        .unwrap();
+    let position = (fs.cluster_to_sector(cluster).unwrap() * fs.bytes_per_sector) as usize + offset;
+    assert_eq!(entry, fs.disk[position..position + 32]);
+
+    let content = fs.read_file("/test.txt", None).unwrap();
+    assert_eq!(
+        u32::from_le_bytes(entry[28..32].try_into().unwrap()),
+        content.len() as u32
+    );
+    assert_eq!(
+        fs.locate_entry("/test.txt", None).unwrap(),
+        fat32_impl::file_system::EntryLocation {
+            cluster,
+            offset_in_cluster: offset,
+        }
+    );
+
+    // Les fragments LFN sont retournés sans être interprétés
+    assert!(
+        fs.iter_dir_raw(root)
+            .any(|(entry, _, _)| entry[0] != 0xE5 && entry[11] == 0x0F)
+    );
+}
+
+#[test_case]
+fn path_of_test() {
+    let fs = init_fs();
+    let mut fs = fs.lock();
+
+    let location = fs.locate_entry("/test_dir/test_dir_file", None).unwrap();
+    assert_eq!(
+        fs.path_of(&location).as_deref(),
+        Some("/test_dir/test_dir_file")
+    );
+
+    // Entrée située dans le second cluster d’un sous-répertoire (16 entrées par cluster)
+    fs.mkdir("/", "DEEP").unwrap();
+    fs.mkdir("/DEEP", "SUB").unwrap();
+    for i in 0..20 {
+        fs.create_file("/DEEP/SUB", &alloc::format!("F{i}.TXT"))
+            .unwrap();
+    }
+    let location = fs.locate_entry("/DEEP/SUB/F19.TXT", None).unwrap();
+    let sub = fs.resolve("/DEEP/SUB", None).unwrap().file.start_cluster;
+    assert_ne!(location.cluster, sub);
+    assert_eq!(fs.path_of(&location).as_deref(), Some("/DEEP/SUB/F19.TXT"));
+
+    // Un slot libre ne désigne aucune entrée
+    let free = fat32_impl::file_system::EntryLocation {
+        cluster: location.cluster,
+        offset_in_cluster: 15 * 32,
+    };
+    assert_eq!(fs.path_of(&free), None);
+}
+
+#[test_case]
+fn rm_glob_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs.clone());
+    shell.run_command("mkdir GLOB").unwrap();
+    shell.cd("GLOB").unwrap();
+    for name in ["a.tmp", "b.tmp", "keep.txt"] {
+        shell
+            .run_command(&alloc::format!("touch {}", name))
+            .unwrap();
+    }
+    shell.run_command("mkdir dir.tmp").unwrap();
+
+    let names = |cluster| -> alloc::vec::Vec<alloc::string::String> {
+        list_directory_entries(&fs.lock(), cluster)
+            .into_iter()
+            .filter(|f| !f.is_dot_entry)
+            .map(|f| f.name)
+            .collect()
+    };
+
+    assert_eq!(shell.rm_glob("*.tmp"), Ok(2));
+    assert_eq!(names(shell.current_cluster), ["KEEP.TXT", "DIR.TMP"]);
+
+    shell.run_command("rm k??p.*").unwrap();
+    assert_eq!(shell.rm_glob("*"), Ok(0));
+    assert_eq!(names(shell.current_cluster), ["DIR.TMP"]);
+
+    assert!(glob_match("*.t*p", "a.tmp"));
+    assert!(!glob_match("*.tmp", "a.tmp.txt"));
+    assert!(!glob_match("?.tmp", "ab.tmp"));
+    assert!(glob_match("*.tmp", "A.TMP"));
+}
+
+#[test_case]
+fn free_count_cached_test() {
+    let fs = init_fs();
+    let mut fs = fs.lock();
+    let before = fs.free_count_cached();
+    assert_eq!(before, fs.recompute_free_count());
+
+    fs.write_file("/CACHED.BIN", None, &[0xAB; 1500]).unwrap();
+    assert_eq!(fs.free_count_cached(), before - 3);
+    fs.mkdir("/", "CACHEDIR").unwrap();
+    assert_eq!(fs.free_count_cached(), fs.recompute_free_count());
+
+    fs.remove_file("/CACHED.BIN", None).unwrap();
+    assert_eq!(fs.free_count_cached(), before - 1);
+    assert_eq!(fs.free_count_cached(), fs.recompute_free_count());
+    assert_eq!(fs.free_size_bytes(), (before as u64 - 1) * 512);
+
+    // Une écriture directe dans la FAT n’est vue qu’après invalidation
+    let entry = (fs.fat_sector * fs.bytes_per_sector) as usize + 4 * 200;
+    assert_eq!(fs.disk[entry..entry + 4], [0; 4]);
+    fs.disk[entry..entry + 4].copy_from_slice(&0x0FFF_FFFFu32.to_le_bytes());
+    assert_eq!(fs.free_count_cached(), before - 1);
+    fs.invalidate_free_count();
+    assert_eq!(fs.free_count_cached(), before - 2);
+}
+
+#[test_case]
+fn ls_all_test() {
+    let fs = init_fs();
+    let mut shell = ShellSession::new(fs);
+    shell.cd("test_dir").unwrap();
+
+    let names = |entries: alloc::vec::Vec<FileInfo>| -> alloc::vec::Vec<alloc::string::String> {
+        entries.into_iter().map(|f| f.name).collect()
+    };
+    assert_eq!(names(shell.ls_entries()), ["test_dir_file"]);
+    assert_eq!(names(shell.ls_entries_all()), [".", "..", "test_dir_file"]);
+
+    shell.start_capture();
+    shell.ls(None).unwrap();
+    assert_eq!(shell.take_output(), "> [FILE] test_dir_file \n");
+    shell.run_command("ls -a").unwrap();
+    assert_eq!(
+        shell.take_output(),
+        "> [DIR] . [DIR] .. [FILE] test_dir_file \n"
+    );
+    shell.cd("..").unwrap();
+    shell.run_command("ls -a").unwrap();
+    shell.ls(None).unwrap();
+    let output = shell.take_output();
+    let (all, default) = output.split_once('\n').unwrap();
+    assert_eq!(alloc::format!("{}\n", all), default);
+}
+
 fn main(boot_info: &'static BootInfo) -> ! {
     use fat32_impl::allocator;
     use fat32_impl::memory::{self, BootInfoFrameAllocator};